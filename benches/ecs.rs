@@ -70,6 +70,14 @@ fn criterion_benchmark(c: &mut Criterion) {
             }
         })
     });
+    c.bench_function("spawn_batch_p_components", |b|{
+        b.iter_batched(Registry::default, |mut registry| {
+            for entity in registry.spawn_batch(p_components.iter().map(|p| P { x: p.x, y: p.y, z: p.z })) {
+                black_box(entity);
+            }
+            registry
+        }, BatchSize::LargeInput)
+    });
 
 }
 
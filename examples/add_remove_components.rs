@@ -18,7 +18,7 @@ struct Rotation {
     w: f32,
 }
 impl Component for Rotation {
-    const NAME: &'static str = "Position";
+    const NAME: &'static str = "Rotation";
 }
 
 #[allow(unused)]
@@ -15,12 +15,41 @@ impl Component for Position {
 }
 
 // You can also use a derive macro if the "derive" feature is enabled:
+// The name of the component includes its module path, e.g. "define_component::DeriveExample".
 #[cfg(feature = "derive")]
 #[derive(Component)]
 struct DeriveExample {
     foo: f32,
 }
 
+// Generic types are supported, each instantiation being a distinct component type.
+// The generic arguments are part of the name, e.g. "define_component::GenericDeriveExample<u32>".
+#[cfg(feature = "derive")]
+#[allow(unused)]
+#[derive(Component)]
+struct GenericDeriveExample<T: Send + Sync> {
+    foo: T,
+}
+
+// The name or id can be overridden to keep them stable when moving or renaming types.
+#[cfg(feature = "derive")]
+#[allow(unused)]
+#[derive(Component)]
+#[shard(name = "StableName")]
+struct RenamedDeriveExample {
+    foo: f32,
+}
+
+// Structs of components can be derived as bundles, usable wherever component groups are.
+// Fields may also be other bundles, which are flattened into a single component group.
+#[cfg(feature = "derive")]
+#[allow(unused)]
+#[derive(Bundle)]
+struct BundleExample {
+    derived: DeriveExample,
+    renamed: RenamedDeriveExample,
+}
+
 fn main() {
     // code ..
 }
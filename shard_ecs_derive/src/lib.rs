@@ -1,16 +1,276 @@
 use proc_macro::*;
-use syn::{DeriveInput, parse_macro_input};
 use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, GenericParam, Index, Lit, LitInt,
+    LitStr, Member, Meta, NestedMeta,
+};
 
-#[proc_macro_derive(Component)]
+/// Derives `Component` for a struct or enum.
+/// The component name consists of the module path and the type name, followed by the generic arguments.
+/// Type arguments are named by `TypeArgumentName`, const arguments are written as integers,
+/// so each instantiation of a generic type is a distinct component type, e.g. `foo::Buffer<u32, 4>`.
+/// Supports the following attributes:
+/// - `#[shard(name = "...")]` overrides the component name, the generic arguments are still appended.
+/// - `#[shard(id = ...)]` overrides the component type id. Not supported for generic types.
+#[proc_macro_derive(Component, attributes(shard))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let (name_override, id_override) = match parse_shard_attributes(&input.attrs) {
+        Ok(v) => v,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let name = input.ident.clone();
+    let base_name = match name_override {
+        Some(v) => quote!(#v),
+        None => quote!(::core::concat!(
+            ::core::module_path!(),
+            "::",
+            ::core::stringify!(#name)
+        )),
+    };
+
+    let mut arguments = Vec::new();
+    for param in input.generics.params.iter() {
+        match param {
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                arguments
+                    .push(quote!(<#ident as ::shard_ecs::TypeArgumentName>::TYPE_ARGUMENT_NAME));
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                arguments
+                    .push(quote!(::shard_ecs::TypeName::from_integer(#ident as i128).as_str()));
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
+    let type_params: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|e| e.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for ident in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ident: ::shard_ecs::TypeArgumentName));
+    }
+
+    let component_name = match arguments.is_empty() {
+        true => base_name,
+        false => quote! {
+            ::shard_ecs::TypeName::with_arguments(#base_name, &[#(#arguments),*]).as_str()
+        },
+    };
+    let component_id = match (id_override, arguments.is_empty()) {
+        (Some(id), true) => quote! {
+            const ID: ::shard_ecs::ComponentTypeId = ::shard_ecs::ComponentTypeId::from_raw(#id);
+        },
+        (Some(id), false) => {
+            return syn::Error::new_spanned(
+                id,
+                "`id` can not be used on generic types, as all instantiations would share it",
+            )
+            .to_compile_error()
+            .into();
+        }
+        (None, _) => quote!(),
+    };
+
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let name = input.ident;
     let expanded = quote! {
-        impl #impl_generics Component for #name #ty_generics #where_clause {
-            const NAME: &'static str = stringify!(#name);
+        impl #impl_generics ::shard_ecs::Component for #name #ty_generics #where_clause {
+            const NAME: &'static str = #component_name;
+            #component_id
         }
     };
     proc_macro::TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Derives `ComponentGroup` for a struct of which every field is a component or another bundle.
+/// Nested bundles are flattened, so the struct is treated as one group of all contained components.
+/// References and slices of a bundle are returned as tuples in field order, with nested bundles as nested tuples.
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let fields = match &input.data {
+        Data::Struct(data) if !data.fields.is_empty() => data.fields.clone(),
+        _ => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "`Bundle` can only be derived for structs with at least one field",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+    let name = input.ident.clone();
+    let members: Vec<Member> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        })
+        .collect();
+    let indices: Vec<Index> = (0..fields.len()).map(Index::from).collect();
+    let types: Vec<_> = fields.iter().map(|field| field.ty.clone()).collect();
+    // Position of the first component of each field within the groups passed to `from_groups`.
+    let offsets: Vec<_> = (0..types.len())
+        .map(|index| {
+            let preceding = &types[..index];
+            quote!(0 #(+ <#preceding as ::shard_ecs::ComponentGroup>::DESCRIPTOR.len() as usize)*)
+        })
+        .collect();
+
+    let where_clause = input.generics.make_where_clause();
+    where_clause.predicates.push(parse_quote!(Self: 'static));
+    for ty in types.iter() {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::shard_ecs::ComponentGroup));
+    }
+
+    let group = quote!(::shard_ecs::ComponentGroup);
+    let subgroup_pointers = quote!(::shard_ecs::descriptors::component_group::subgroup_pointers);
+    let write_subgroup_pointers =
+        quote!(::shard_ecs::descriptors::component_group::write_subgroup_pointers);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let expanded = quote! {
+        unsafe impl #impl_generics #group for #name #ty_generics #where_clause {
+            type RefTuple<'c> = (#(<#types as #group>::RefTuple<'c>,)*) where Self: 'c;
+            type MutRefTuple<'c> = (#(<#types as #group>::MutRefTuple<'c>,)*) where Self: 'c;
+            type SliceRefTuple<'c> = (#(<#types as #group>::SliceRefTuple<'c>,)*) where Self: 'c;
+            type SliceMutRefTuple<'c> = (#(<#types as #group>::SliceMutRefTuple<'c>,)*) where Self: 'c;
+
+            const DESCRIPTOR: ::shard_ecs::ComponentGroupDescriptor =
+                ::shard_ecs::ComponentGroupDescriptor::from_groups(&[
+                    #(&<#types as #group>::DESCRIPTOR),*
+                ]);
+
+            unsafe fn as_sorted_pointers(
+                &mut self,
+                ptrs: &mut [*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+            ) {
+                #(#write_subgroup_pointers::<Self, #types>(&mut self.#members, ptrs, #offsets);)*
+            }
+
+            unsafe fn read_from_sorted_pointers(
+                pointers: &[*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+            ) -> Self {
+                Self {
+                    #(#members: <#types as #group>::read_from_sorted_pointers(
+                        &#subgroup_pointers::<Self, #types>(pointers, #offsets),
+                    ),)*
+                }
+            }
+
+            unsafe fn pointers_as_ref_tuple<'a, 'b>(
+                sorted_pointers: &'a [*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+            ) -> Self::RefTuple<'b> {
+                (#(<#types as #group>::pointers_as_ref_tuple(
+                    &#subgroup_pointers::<Self, #types>(sorted_pointers, #offsets),
+                ),)*)
+            }
+
+            unsafe fn pointers_as_mut_ref_tuple<'a, 'b>(
+                sorted_pointers: &'a [*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+            ) -> Self::MutRefTuple<'b> {
+                (#(<#types as #group>::pointers_as_mut_ref_tuple(
+                    &#subgroup_pointers::<Self, #types>(sorted_pointers, #offsets),
+                ),)*)
+            }
+
+            unsafe fn slice_unchecked<'a, 'b>(
+                sorted_pointers: &'a [*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+                len: usize,
+            ) -> Self::SliceRefTuple<'b> {
+                (#(<#types as #group>::slice_unchecked(
+                    &#subgroup_pointers::<Self, #types>(sorted_pointers, #offsets),
+                    len,
+                ),)*)
+            }
+
+            unsafe fn slice_unchecked_mut<'a, 'b>(
+                sorted_pointers: &'a [*mut u8; ::shard_ecs::MAX_COMPONENTS_PER_ENTITY],
+                len: usize,
+            ) -> Self::SliceMutRefTuple<'b> {
+                (#(<#types as #group>::slice_unchecked_mut(
+                    &#subgroup_pointers::<Self, #types>(sorted_pointers, #offsets),
+                    len,
+                ),)*)
+            }
+
+            unsafe fn slice_get_unchecked<'a>(
+                slices: &Self::SliceRefTuple<'a>,
+                index: usize,
+            ) -> Self::RefTuple<'a> {
+                (#(<#types as #group>::slice_get_unchecked(&slices.#indices, index),)*)
+            }
+
+            unsafe fn slice_get_unchecked_mut<'a>(
+                slices: &mut Self::SliceMutRefTuple<'a>,
+                index: usize,
+            ) -> Self::MutRefTuple<'a> {
+                (#(<#types as #group>::slice_get_unchecked_mut(&mut slices.#indices, index),)*)
+            }
+
+            fn split_slices_at_mut<'a>(
+                slices: Self::SliceMutRefTuple<'a>,
+                index: usize,
+            ) -> (Self::SliceMutRefTuple<'a>, Self::SliceMutRefTuple<'a>) {
+                let split = (#(<#types as #group>::split_slices_at_mut(slices.#indices, index),)*);
+                ((#(split.#indices.0,)*), (#(split.#indices.1,)*))
+            }
+
+            fn empty_slice<'a>() -> Self::SliceRefTuple<'a> {
+                (#(<#types as #group>::empty_slice(),)*)
+            }
+
+            fn empty_slice_mut<'a>() -> Self::SliceMutRefTuple<'a> {
+                (#(<#types as #group>::empty_slice_mut(),)*)
+            }
+        }
+    };
+    proc_macro::TokenStream::from(expanded)
+}
+
+/// Parses the `#[shard(name = "...", id = ...)]` attributes.
+fn parse_shard_attributes(attrs: &[Attribute]) -> syn::Result<(Option<LitStr>, Option<LitInt>)> {
+    let mut name = None;
+    let mut id = None;
+    for attr in attrs.iter().filter(|e| e.path.is_ident("shard")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected `#[shard(...)]`")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("name") => {
+                    match value.lit {
+                        Lit::Str(lit) => name = Some(lit),
+                        lit => {
+                            return Err(syn::Error::new_spanned(lit, "expected a string literal"))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("id") => {
+                    match value.lit {
+                        Lit::Int(lit) => id = Some(lit),
+                        lit => {
+                            return Err(syn::Error::new_spanned(lit, "expected an integer literal"))
+                        }
+                    }
+                }
+                nested => {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "unknown attribute, expected `name = \"...\"` or `id = ...`",
+                    ))
+                }
+            }
+        }
+    }
+    Ok((name, id))
+}
@@ -0,0 +1,59 @@
+use alloc::alloc::Layout;
+
+/// Allocates the backing memory of archetypes, which stores the component columns and entity associations.
+/// Every archetype keeps its own clone of the allocator, so allocators carrying state (e.g. a handle to an arena)
+/// should be cheap to clone and refer to the same memory pool.
+/// # Safety
+/// - Implementations must uphold the same contract as [`core::alloc::GlobalAlloc`].
+/// - Memory allocated by an allocator must be deallocatable by all of its clones.
+pub unsafe trait ArchetypeAllocator: Clone {
+    /// Allocates memory as described by the given layout.
+    /// Returns a null pointer if the allocation failed.
+    /// # Safety
+    /// - [`layout`] must have a non-zero size.
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8;
+
+    /// Deallocates the memory at [`ptr`].
+    /// # Safety
+    /// - [`ptr`] must have been allocated by this allocator or one of its clones using [`layout`].
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+
+    /// Shrinks or grows the memory at [`ptr`] to [`new_size`] bytes, preserving its contents.
+    /// Returns a null pointer if the allocation failed, in which case the old memory is left untouched.
+    /// By default, allocates a new block, copies the contents and deallocates the old block.
+    /// # Safety
+    /// - [`ptr`] must have been allocated by this allocator or one of its clones using [`layout`].
+    /// - [`new_size`] must be non-zero.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.alloc(new_layout);
+        if !new_ptr.is_null() {
+            core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.dealloc(ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+/// The global allocator, which is used by default for storing archetypes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl ArchetypeAllocator for Global {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        alloc::alloc::alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        alloc::alloc::dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        alloc::alloc::realloc(ptr, layout, new_size)
+    }
+}
+
+/// Error returned when the backing memory of an archetype could not be allocated.
+/// The archetype is left untouched when this error is returned.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
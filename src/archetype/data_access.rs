@@ -1,14 +1,22 @@
+use super::ticks::TickColumns;
 use super::Archetype;
 use crate::*;
 
+use crate::allocator::{AllocError, ArchetypeAllocator};
 use crate::constants::DEFAULT_ARCHETYPE_ALLOCATION_SIZE;
-use alloc::alloc::{dealloc, realloc, Layout};
+use alloc::alloc::Layout;
 use core::mem::{align_of, size_of};
 use core::ptr::{slice_from_raw_parts, slice_from_raw_parts_mut};
 
-impl Archetype {
+/// Pointer to a pointer to backing memory, alongside the size and alignment of its elements.
+pub(super) type MemoryBlock = (*mut *mut u8, usize, usize);
+
+/// The entity associations, a column for each component and a tick column for each component.
+const MAX_MEMORY_BLOCKS: usize = 1 + 2 * MAX_COMPONENTS_PER_ENTITY;
+
+impl<A: ArchetypeAllocator> Archetype<A> {
     /// Returns a reference to a specific component.
-    /// # Safety:
+    /// # Safety
     /// - Component type [`C`] must be present in the archetype
     /// - panics otherwise.
     pub unsafe fn get_component_unchecked<C: Component>(&self, index: u32) -> &C {
@@ -23,7 +31,7 @@ impl Archetype {
     }
 
     /// Returns a mutable reference to a specific component.
-    /// # Safety:
+    /// # Safety
     /// - Component type [`C`] must be present in the archetype
     /// - panics otherwise.
     pub unsafe fn get_component_unchecked_mut<C: Component>(&mut self, index: u32) -> &mut C {
@@ -38,7 +46,7 @@ impl Archetype {
     }
 
     /// Returns a reference to a specific component.
-    /// # Safety:
+    /// # Safety
     /// - Component group type [`G`] must be a subset of the types in the archetype
     /// - panics otherwise.
     pub unsafe fn get_fuzzy_components_unchecked<'a, G: ComponentGroup>(
@@ -51,7 +59,7 @@ impl Archetype {
     }
 
     /// Returns a reference to a specific component.
-    /// # Safety:
+    /// # Safety
     /// - Component group type [`G`] must be a subset of the types in the archetype
     /// - panics otherwise.
     pub unsafe fn get_fuzzy_components_unchecked_mut<'a, G: ComponentGroup>(
@@ -64,7 +72,7 @@ impl Archetype {
     }
 
     /// Reads a specific component from the archetype at the given index.
-    /// # Safety:
+    /// # Safety
     /// - Component type [`C`] must be present in the archetype
     /// - panics otherwise.
     pub unsafe fn read_component_unchecked<C: Component>(&mut self, index: u32) -> C {
@@ -80,8 +88,19 @@ impl Archetype {
         }
     }
 
+    /// Reads the components in [`G`] from the archetype at the given index.
+    /// # Safety
+    /// - Component group type [`G`] must be a subset of the types in the archetype.
+    /// - [`index`] must be smaller than the archetype's capacity.
+    /// - The read components must not be dropped again by the archetype.
+    pub unsafe fn read_fuzzy_components_unchecked<G: ComponentGroup>(&self, index: u32) -> G {
+        debug_assert!(G::DESCRIPTOR.is_valid());
+        let pointers = self.get_fuzzy_pointers_unchecked::<G>(index);
+        G::read_from_sorted_pointers(&pointers)
+    }
+
     /// Returns a tuple of mutable component slices to the archetype's data.
-    /// # Safety:
+    /// # Safety
     /// - Must be called exactly with the component group contained in the archetype.
     /// - a compatible group type is also accepted.
     /// - [`G`] must have a valid archetype descriptor.
@@ -97,7 +116,7 @@ impl Archetype {
     }
 
     /// Returns a tuple of component slices to the archetype's data.
-    /// # Safety:
+    /// # Safety
     /// - Must be called exactly with the component group contained in the archetype.
     /// - a compatible group type is also accepted.
     /// - [`G`] must have a valid archetype descriptor.
@@ -114,7 +133,7 @@ impl Archetype {
 
     /// Returns the slices for the components in [`G`], provided that archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
-    /// # Safety:
+    /// # Safety
     /// - Only call this with subsets of the types stored in the archetype.
     /// - [`G`] must have a valid archetype descriptor.
     pub unsafe fn get_fuzzy_slices_unchecked<'s, G: ComponentGroup>(
@@ -127,7 +146,7 @@ impl Archetype {
 
     /// Returns the mutable slices for the components in [`G`], provided that archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
-    /// # Safety:
+    /// # Safety
     /// - Only call this with subsets of the types stored in the archetype.
     /// - [`G`] must have a valid archetype descriptor.
     pub unsafe fn get_fuzzy_slices_unchecked_mut<'s, G: ComponentGroup>(
@@ -141,7 +160,7 @@ impl Archetype {
     /// Returns the entity slice and the  slices for the components in [`G`], provided that archetype
     /// itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
-    /// # Safety:
+    /// # Safety
     /// - Only call this with subsets of the types stored in the archetype.
     /// - [`G`] must have a valid archetype descriptor.
     pub unsafe fn get_entity_fuzzy_slices_unchecked<'s, G: ComponentGroup>(
@@ -158,7 +177,7 @@ impl Archetype {
     /// Returns the entity slice and the  mutable slices for the components in [`G`], provided that
     /// archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
-    /// # Safety:
+    /// # Safety
     /// - Only call this with subsets of the types stored in the archetype.
     /// - [`G`] must have a valid archetype descriptor.
     pub unsafe fn get_entity_fuzzy_slices_unchecked_mut<'s, G: ComponentGroup>(
@@ -173,7 +192,7 @@ impl Archetype {
     }
 }
 
-impl Archetype {
+impl<A: ArchetypeAllocator> Archetype<A> {
     /// Returns the amount of entities currently stored in the archetype.
     pub fn len(&self) -> u32 {
         self.entity_count
@@ -189,6 +208,61 @@ impl Archetype {
         self.entity_count == self.capacity()
     }
 
+    /// Reserves capacity for at least [`additional`] more entities, limited by [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does nothing if the capacity is already sufficient.
+    /// Panics in case of allocation failure.
+    pub fn reserve(&mut self, additional: u32) {
+        let required = (self.len() as usize + additional as usize)
+            .min(MAX_ENTITIES_PER_ARCHETYPE as usize - 1);
+        if required > self.capacity() as usize {
+            unsafe { self.resize_capacity(required as isize - self.capacity() as isize) };
+        }
+    }
+
+    /// Reserves capacity for at least [`additional`] more entities, limited by [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does nothing if the capacity is already sufficient.
+    /// Returns an error if the allocation fails, in which case the archetype is left untouched.
+    pub fn try_reserve(&mut self, additional: u32) -> Result<(), AllocError> {
+        let required = (self.len() as usize + additional as usize)
+            .min(MAX_ENTITIES_PER_ARCHETYPE as usize - 1);
+        if required > self.capacity() as usize {
+            unsafe { self.try_set_capacity(required as u32)? };
+        }
+        Ok(())
+    }
+
+    /// Shrinks the capacity of the archetype as close as possible to the amount of entities in it.
+    /// Keeps room for at least a single entity.
+    /// Panics in case of allocation failure.
+    pub fn shrink_to_fit(&mut self) {
+        let required = self.len().max(1);
+        if required < self.capacity() {
+            unsafe { self.resize_capacity(required as isize - self.capacity() as isize) };
+        }
+        self.edges.shrink_to_fit();
+    }
+
+    /// Returns the amount of bytes used to store a single entity including its components.
+    pub fn entity_size(&self) -> usize {
+        let components: usize = self
+            .descriptor
+            .components()
+            .iter()
+            .map(|component| component.size as usize)
+            .sum();
+        size_of::<Entity>() + components + TickColumns::entity_size(self.descriptor.len() as usize)
+    }
+
+    /// Returns the amount of bytes allocated for storing entities and their components.
+    pub fn allocated_bytes(&self) -> usize {
+        self.capacity() as usize * self.entity_size()
+    }
+
+    /// Returns the amount of bytes occupied by the entities currently stored in the archetype.
+    pub fn used_bytes(&self) -> usize {
+        self.len() as usize * self.entity_size()
+    }
+
     /// Returns a reference to the internal slice storing entity associations.
     pub fn entities(&self) -> &[Entity] {
         unsafe { &*slice_from_raw_parts(self.entity_associations, self.len() as usize) }
@@ -200,7 +274,7 @@ impl Archetype {
     }
 
     /// Pushes a given entity/component-tuple into the archetype's backing memory.
-    /// # Safety:
+    /// # Safety
     /// - Must be called exactly with the component group contained in the archetype.
     /// - a compatible group type is also accepted.
     /// - Does not call drop on the given entity.
@@ -260,8 +334,32 @@ impl Archetype {
         }
     }
 
+    /// Writes the components in [`G`] into a specific position.
+    /// Does not call drop on the existing components at index.
+    /// # Safety
+    /// - Component group type [`G`] must be a subset of the types in the archetype.
+    /// - [`index`] must be smaller than the archetype's capacity.
+    pub unsafe fn write_fuzzy_components_unchecked<G: ComponentGroup>(
+        &mut self,
+        index: u32,
+        mut components: G,
+    ) {
+        debug_assert!(G::DESCRIPTOR.is_valid());
+        let destination_pointers = self.get_fuzzy_pointers_unchecked::<G>(index);
+        let mut source_pointers = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+        components.as_sorted_pointers(&mut source_pointers);
+        for (i, component) in G::DESCRIPTOR.archetype().components().iter().enumerate() {
+            core::ptr::copy_nonoverlapping::<u8>(
+                source_pointers[i],
+                destination_pointers[i],
+                component.size as usize,
+            );
+        }
+        core::mem::forget(components);
+    }
+
     /// Writes a given entity/component-tuple into the archetype's backing memory.
-    /// # Safety:
+    /// # Safety
     /// - Must be called exactly with the component group contained in the archetype.
     /// - a compatible group type is also accepted.
     /// - Does not call drop on the given entity.
@@ -308,7 +406,7 @@ impl Archetype {
     /// And moving the previously last entity to the position at index.
     /// If [`index`] is the last element, simply drops it instead without any swaps occurring.
     /// Returns true if a swap occurred, or false if not.
-    /// # Safety:
+    /// # Safety
     /// - [`index`] must be smaller than the amount of entities in the archetype.
     pub unsafe fn swap_drop_unchecked(&mut self, index: u32) -> bool {
         debug_assert!(index < self.len());
@@ -329,7 +427,7 @@ impl Archetype {
     /// Makes sure the entity at [`index`] is at the end of the archetype.
     /// If [`index`] is the last element, does nothing.
     /// Returns true if a swap occurred, or false if not.
-    /// # Safety:
+    /// # Safety
     /// - [`index`] must be smaller than the amount of entities in the archetype.
     pub unsafe fn swap_to_last_unchecked(&mut self, index: u32) -> bool {
         debug_assert!(index < self.len());
@@ -346,7 +444,7 @@ impl Archetype {
     /// And moving the previously last entity to the position at index.
     /// If [`index`] is the last element, simply returns it instead without any swaps occurring.
     /// Returns true if a swap occurred, or false if not.
-    /// # Safety:
+    /// # Safety
     /// - [`index`] must be smaller than the amount of entities in the archetype.
     /// - [`G`] must exactly match the type store in the archetype.
     /// - Ordering of component in [`G`] may be different.
@@ -366,7 +464,7 @@ impl Archetype {
     }
 
     /// Swaps the entities at the provided positions.
-    /// # Safety:
+    /// # Safety
     /// - [`first`] must be smaller than the amount of entities in the archetype.
     /// - [`second`] must be smaller than the amount of entities in the archetype.
     /// - [`first`] must not be equal to [`last`].
@@ -376,11 +474,13 @@ impl Archetype {
             let ptr_second = self.pointers[idx].offset(second as isize * descriptor.size as isize);
             core::ptr::swap_nonoverlapping(ptr_first, ptr_second, descriptor.size as usize);
         }
+        self.ticks
+            .swap(self.descriptor.len() as usize, first, second);
         self.entities_mut().swap(first as usize, second as usize);
     }
 
     /// Calls drop on the entity at [`index`].
-    /// # Safety:
+    /// # Safety
     /// - [`index`] must be smaller than the amount of entities in the archetype.
     pub unsafe fn drop_entity(&mut self, index: u32) {
         for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
@@ -399,8 +499,34 @@ impl Archetype {
         }
     }
 
+    /// Drops all the entities at index [`len`] and beyond, shortening the archetype to [`len`] entities.
+    /// Does nothing if [`len`] is greater than or equal to the amount of entities in the archetype.
+    /// Does not deallocate the memory.
+    pub fn truncate(&mut self, len: u32) {
+        if len >= self.len() {
+            return;
+        }
+        let dropped = self.len() - len;
+        // Shorten first, so a panicking drop leaks the remaining entities instead of dropping them twice.
+        self.entity_count = len;
+        for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+            unsafe {
+                (descriptor.fns.drop_handler)(
+                    self.pointers[idx].offset(len as isize * descriptor.size as isize),
+                    dropped as usize,
+                );
+            }
+        }
+    }
+
+    /// Drops all the entities in the archetype and sets its length to zero.
+    /// Does not deallocate the memory.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
     /// Reads the component data at [`index`] and returns it.
-    /// # Safety:
+    /// # Safety
     /// - [`G`] must be exactly the type stored in the archetype.
     /// - a compatible one also works. (i.e. same archetype, different ordering)
     pub unsafe fn read_components_exact_unchecked<G: ComponentGroup>(&self, index: u32) -> G {
@@ -409,10 +535,10 @@ impl Archetype {
     }
 }
 
-impl Archetype {
+impl<A: ArchetypeAllocator> Archetype<A> {
     /// Resizes the backing memory by some amount. If this becomes less than or equal to zero,
     /// deallocates all memory.
-    /// # Safety:
+    /// # Safety
     /// - Does not call drop on the entities in the backing storage.
     /// - Panics if resizing fails for whatever reason. This leaves the archetype in an undefined state.
     /// - Deallocates if the new capacity is smaller or equal to zero.
@@ -426,39 +552,113 @@ impl Archetype {
         }
         let new_capacity = new_capacity as usize;
 
-        let layout = Layout::from_size_align_unchecked(
-            size_of::<Entity>() * old_capacity as usize,
+        let (blocks, block_count) = self.memory_blocks();
+        for &(pointer, size, align) in blocks[0..block_count].iter() {
+            let layout = Layout::from_size_align_unchecked(size * old_capacity as usize, align);
+            *pointer =
+                Self::reallocate_with(&self.allocator, *pointer, layout, size * new_capacity);
+            assert_ne!(*pointer, core::ptr::null_mut());
+        }
+        self.capacity = new_capacity as u32;
+    }
+
+    /// Resizes the backing memory to exactly [`new_capacity`] entities.
+    /// Unlike [`Archetype::resize_capacity`], allocates all new memory before releasing the old memory.
+    /// Returns an error if any allocation fails, in which case the archetype is left untouched.
+    /// # Safety
+    /// - [`new_capacity`] must be larger than zero and at least the amount of entities in the archetype.
+    pub(super) unsafe fn try_set_capacity(&mut self, new_capacity: u32) -> Result<(), AllocError> {
+        let old_capacity = self.capacity() as usize;
+        let new_capacity_usize = new_capacity as usize;
+        let (blocks, block_count) = self.memory_blocks();
+
+        // Allocate all new blocks up front, so a failure leaves the archetype untouched.
+        let mut new_pointers = [core::ptr::null_mut::<u8>(); MAX_MEMORY_BLOCKS];
+        for (index, &(_, size, align)) in blocks[0..block_count].iter().enumerate() {
+            new_pointers[index] = self.allocator.alloc(Layout::from_size_align_unchecked(
+                size * new_capacity_usize,
+                align,
+            ));
+            if new_pointers[index].is_null() {
+                for (&(_, size, align), new_pointer) in
+                    blocks[0..index].iter().zip(new_pointers.iter())
+                {
+                    self.allocator.dealloc(
+                        *new_pointer,
+                        Layout::from_size_align_unchecked(size * new_capacity_usize, align),
+                    );
+                }
+                return Err(AllocError);
+            }
+        }
+
+        // Move the stored entities over and release the old blocks.
+        for (&(pointer, size, align), new_pointer) in
+            blocks[0..block_count].iter().zip(new_pointers.iter())
+        {
+            if old_capacity > 0 {
+                core::ptr::copy_nonoverlapping(*pointer, *new_pointer, size * self.len() as usize);
+                self.allocator.dealloc(
+                    *pointer,
+                    Layout::from_size_align_unchecked(size * old_capacity, align),
+                );
+            }
+            *pointer = *new_pointer;
+        }
+        self.capacity = new_capacity;
+        Ok(())
+    }
+
+    /// Returns pointers to every pointer to backing memory, alongside the size and alignment of its elements.
+    /// Only the first returned amount of blocks is valid.
+    fn memory_blocks(&mut self) -> ([MemoryBlock; MAX_MEMORY_BLOCKS], usize) {
+        let columns = self.descriptor.len() as usize;
+        let mut blocks = [(core::ptr::null_mut::<*mut u8>(), 0, 1); MAX_MEMORY_BLOCKS];
+        blocks[0] = (
+            &mut self.entity_associations as *mut *mut Entity as *mut *mut u8,
+            size_of::<Entity>(),
             align_of::<Entity>(),
         );
-        self.entity_associations = realloc(
-            self.entity_associations as *mut u8,
-            layout,
-            size_of::<Entity>() * new_capacity,
-        ) as *mut Entity;
-        assert_ne!(self.entity_associations, core::ptr::null_mut());
-        for (index, pointer) in self.pointers[0..self.descriptor.len() as usize]
-            .iter_mut()
-            .enumerate()
-        {
+        let mut block_count = 1;
+        for (index, pointer) in self.pointers[0..columns].iter_mut().enumerate() {
             let component_type = &self.descriptor.components()[index];
-            let layout = Layout::from_size_align_unchecked(
-                component_type.size as usize * old_capacity as usize,
+            blocks[block_count] = (
+                pointer as *mut *mut u8,
+                component_type.size as usize,
                 component_type.align as usize,
             );
-            *pointer = realloc(
-                *pointer,
-                layout,
-                component_type.size as usize * new_capacity,
-            );
-            assert_ne!(*pointer, core::ptr::null_mut());
+            block_count += 1;
+        }
+        for block in self.ticks.blocks_mut(columns) {
+            blocks[block_count] = block;
+            block_count += 1;
+        }
+        (blocks, block_count)
+    }
+
+    /// Reallocates the memory at [`pointer`] using [`allocator`], allocating new memory if nothing was allocated yet.
+    unsafe fn reallocate_with(
+        allocator: &A,
+        pointer: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        if layout.size() == 0 {
+            allocator.alloc(Layout::from_size_align_unchecked(new_size, layout.align()))
+        } else {
+            allocator.realloc(pointer, layout, new_size)
         }
-        self.capacity = new_capacity as u32;
     }
 
     /// Deallocates the backing memory and sets capacity to zero.
-    /// # Safety:
+    /// # Safety
     /// - Does not call drop on the entities in the backing storage.
     pub(super) unsafe fn dealloc(&mut self) {
+        self.ticks.dealloc(
+            &self.allocator,
+            self.descriptor.len() as usize,
+            self.capacity,
+        );
         for (index, pointer) in self.pointers[0..self.descriptor.len() as usize]
             .iter_mut()
             .enumerate()
@@ -471,14 +671,15 @@ impl Archetype {
                 component_type.size as usize * self.capacity as usize,
                 component_type.align as usize,
             );
-            dealloc(*pointer, layout);
+            self.allocator.dealloc(*pointer, layout);
             *pointer = core::ptr::null_mut();
         }
         let layout = Layout::from_size_align_unchecked(
             size_of::<Entity>() * self.capacity() as usize,
             align_of::<Entity>(),
         );
-        dealloc(self.entity_associations as *mut u8, layout);
+        self.allocator
+            .dealloc(self.entity_associations as *mut u8, layout);
         self.entity_associations = core::ptr::null_mut();
         self.capacity = 0;
     }
@@ -511,11 +712,29 @@ impl Archetype {
         }
     }
 
+    /// Resizes the backing memory by the default amount if necessary, limited by [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Returns an error if the allocation fails, in which case the archetype is left untouched.
+    pub(crate) fn try_resize_if_necessary(&mut self) -> Result<(), AllocError> {
+        if self.is_full() {
+            let additional_capacity = if self.capacity() == 0 {
+                DEFAULT_ARCHETYPE_ALLOCATION_SIZE
+            } else {
+                self.capacity() as usize
+            };
+            let new_capacity = (self.capacity() as usize + additional_capacity)
+                .min(MAX_ENTITIES_PER_ARCHETYPE as usize - 1);
+            if new_capacity > self.capacity() as usize {
+                unsafe { self.try_set_capacity(new_capacity as u32)? };
+            }
+        }
+        Ok(())
+    }
+
     /// Copies common components between two archetypes.
     pub unsafe fn copy_common_components_between_archetypes_unchecked(
-        source: &Archetype,
+        source: &Archetype<A>,
         source_index: u32,
-        destination: &mut Archetype,
+        destination: &mut Archetype<A>,
         destination_index: u32,
     ) {
         for (source_c_idx, source_component) in source.descriptor.components().iter().enumerate() {
@@ -532,13 +751,30 @@ impl Archetype {
                         .offset(destination_component.size as isize * destination_index as isize),
                     source_component.size as usize,
                 );
+                TickColumns::copy(
+                    &source.ticks,
+                    source_c_idx,
+                    source_index,
+                    &mut destination.ticks,
+                    destination_c_idx,
+                    destination_index,
+                );
             }
         }
     }
 
+    /// Returns the pointer to the start of the column of the given component type.
+    /// Returns a null pointer if the component type is not present in the archetype.
+    pub(crate) fn column_pointer(&self, component: &ComponentDescriptor) -> *mut u8 {
+        match self.descriptor.component_index(component) {
+            Some(idx) => self.pointers[idx],
+            None => core::ptr::null_mut(),
+        }
+    }
+
     /// Returns the pointers for the components in [`G`], provided that archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
-    /// # Safety:
+    /// # Safety
     /// - offset must be smaller than self.capacity.
     /// - Only call this with subsets of the types stored in the shard.
     unsafe fn get_fuzzy_pointers_unchecked<'a, G: ComponentGroup>(
@@ -553,8 +789,8 @@ impl Archetype {
                     .components_unchecked()
                     .get_unchecked(check_index)
                     .component_type_id
-                    .into_u16()
-                    == descriptor.component_type_id.into_u16()
+                    .into_raw()
+                    == descriptor.component_type_id.into_raw()
                 {
                     *pointers.get_unchecked_mut(index) = self
                         .pointers
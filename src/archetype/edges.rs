@@ -0,0 +1,91 @@
+use super::Archetype;
+use crate::allocator::ArchetypeAllocator;
+use crate::descriptors::component_type_id::ComponentTypeId;
+use crate::INVALID_ARCHETYPE_INDEX;
+use alloc::collections::TryReserveError;
+
+/// Caches the archetypes reached by adding or removing a single component type.
+/// Unknown transitions are stored as [`INVALID_ARCHETYPE_INDEX`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ArchetypeEdge {
+    component_type_id: ComponentTypeId,
+    add: u16,
+    remove: u16,
+}
+
+impl ArchetypeEdge {
+    const fn new(component_type_id: ComponentTypeId) -> Self {
+        Self {
+            component_type_id,
+            add: INVALID_ARCHETYPE_INDEX,
+            remove: INVALID_ARCHETYPE_INDEX,
+        }
+    }
+}
+
+impl<A: ArchetypeAllocator> Archetype<A> {
+    /// Returns the index of the archetype reached by adding the given component type, if it is cached.
+    pub fn add_edge(&self, component_type_id: ComponentTypeId) -> Option<u16> {
+        match self.find_edge(component_type_id)?.add {
+            INVALID_ARCHETYPE_INDEX => None,
+            index => Some(index),
+        }
+    }
+
+    /// Returns the index of the archetype reached by removing the given component type, if it is cached.
+    pub fn remove_edge(&self, component_type_id: ComponentTypeId) -> Option<u16> {
+        match self.find_edge(component_type_id)?.remove {
+            INVALID_ARCHETYPE_INDEX => None,
+            index => Some(index),
+        }
+    }
+
+    /// Reserves room for caching one more transition, so the next call to [`Archetype::set_add_edge`]
+    /// or [`Archetype::set_remove_edge`] does not allocate.
+    pub(crate) fn try_reserve_edge(&mut self) -> Result<(), TryReserveError> {
+        self.edges.try_reserve(1)
+    }
+
+    /// Caches the archetype reached by adding the given component type.
+    pub(crate) fn set_add_edge(
+        &mut self,
+        component_type_id: ComponentTypeId,
+        archetype_index: u16,
+    ) {
+        self.find_or_insert_edge(component_type_id).add = archetype_index;
+    }
+
+    /// Caches the archetype reached by removing the given component type.
+    pub(crate) fn set_remove_edge(
+        &mut self,
+        component_type_id: ComponentTypeId,
+        archetype_index: u16,
+    ) {
+        self.find_or_insert_edge(component_type_id).remove = archetype_index;
+    }
+
+    fn find_edge(&self, component_type_id: ComponentTypeId) -> Option<&ArchetypeEdge> {
+        match self
+            .edges
+            .binary_search_by_key(&component_type_id, |e| e.component_type_id)
+        {
+            Ok(index) => Some(&self.edges[index]),
+            Err(_) => None,
+        }
+    }
+
+    fn find_or_insert_edge(&mut self, component_type_id: ComponentTypeId) -> &mut ArchetypeEdge {
+        let index = match self
+            .edges
+            .binary_search_by_key(&component_type_id, |e| e.component_type_id)
+        {
+            Ok(index) => index,
+            Err(insertion_index) => {
+                self.edges
+                    .insert(insertion_index, ArchetypeEdge::new(component_type_id));
+                insertion_index
+            }
+        };
+        &mut self.edges[index]
+    }
+}
@@ -1,43 +1,88 @@
+use crate::allocator::{AllocError, ArchetypeAllocator, Global};
 use crate::constants::DEFAULT_ARCHETYPE_ALLOCATION_SIZE;
 use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
 use crate::*;
-use alloc::alloc::{alloc, Layout};
-use core::mem::{align_of, size_of};
+use alloc::vec::Vec;
 
 mod data_access;
+mod edges;
 #[cfg(test)]
 mod tests;
+mod ticks;
+
+#[cfg(feature = "change_detection")]
+pub use ticks::ComponentTicks;
+pub(crate) use ticks::TickColumn;
 
 #[derive(Debug)]
-pub struct Archetype {
+pub struct Archetype<A: ArchetypeAllocator = Global> {
     descriptor: ArchetypeDescriptor,
     pointers: [*mut u8; MAX_COMPONENTS_PER_ENTITY],
     entity_associations: *mut Entity,
     entity_count: u32,
     capacity: u32,
+    edges: Vec<edges::ArchetypeEdge>,
+    ticks: ticks::TickColumns,
+    allocator: A,
 }
 
-unsafe impl Send for Archetype {}
-unsafe impl Sync for Archetype {}
+unsafe impl<A: ArchetypeAllocator + Send> Send for Archetype<A> {}
+unsafe impl<A: ArchetypeAllocator + Sync> Sync for Archetype<A> {}
 
 impl Archetype {
     /// Creates an archetype with the default amount of memory allocated for it.
     /// Panics if the provided archetype descriptor is invalid.
     #[allow(dead_code)]
     pub fn new(archetype_descriptor: &ArchetypeDescriptor) -> Archetype {
+        Self::new_in(archetype_descriptor, Global)
+    }
+
+    /// Allocates an archetype with a given capacity for storing data into it.
+    /// Panics if the provided archetype descriptor is invalid.
+    /// Does not allocate if [`capacity`] exceeds [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does not allocate if [`capacity`] is 0.
+    /// Panics in case of allocation failures.
+    pub fn with_capacity(archetype_descriptor: &ArchetypeDescriptor, capacity: u32) -> Archetype {
+        Self::with_capacity_in(archetype_descriptor, capacity, Global)
+    }
+}
+
+impl<A: ArchetypeAllocator> Archetype<A> {
+    /// Creates an archetype with the default amount of memory allocated for it using the given allocator.
+    /// Panics if the provided archetype descriptor is invalid.
+    pub fn new_in(archetype_descriptor: &ArchetypeDescriptor, allocator: A) -> Archetype<A> {
         assert!(archetype_descriptor.is_valid());
-        Self::with_capacity(
+        Self::with_capacity_in(
             archetype_descriptor,
             DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32,
+            allocator,
         )
     }
 
-    /// Allocates an archetype with a given capacity for storing data into it.
+    /// Allocates an archetype with a given capacity for storing data into it using the given allocator.
     /// Panics if the provided archetype descriptor is invalid.
     /// Does not allocate if [`capacity`] exceeds [`MAX_ENTITIES_PER_ARCHETYPE`].
     /// Does not allocate if [`capacity`] is 0.
     /// Panics in case of allocation failures.
-    pub fn with_capacity(archetype_descriptor: &ArchetypeDescriptor, capacity: u32) -> Archetype {
+    pub fn with_capacity_in(
+        archetype_descriptor: &ArchetypeDescriptor,
+        capacity: u32,
+        allocator: A,
+    ) -> Archetype<A> {
+        Self::try_with_capacity_in(archetype_descriptor, capacity, allocator)
+            .expect("Archetype allocation failed.")
+    }
+
+    /// Allocates an archetype with a given capacity for storing data into it using the given allocator.
+    /// Returns an error instead of panicking in case of allocation failures.
+    /// Panics if the provided archetype descriptor is invalid.
+    /// Does not allocate if [`capacity`] exceeds [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does not allocate if [`capacity`] is 0.
+    pub fn try_with_capacity_in(
+        archetype_descriptor: &ArchetypeDescriptor,
+        capacity: u32,
+        allocator: A,
+    ) -> Result<Archetype<A>, AllocError> {
         assert!(archetype_descriptor.is_valid());
         let mut archetype = Self {
             descriptor: archetype_descriptor.clone(),
@@ -45,29 +90,20 @@ impl Archetype {
             entity_associations: core::ptr::null_mut(),
             entity_count: 0,
             capacity: 0,
+            edges: Vec::new(),
+            ticks: ticks::TickColumns::new(),
+            allocator,
         };
         // Allocate
         if capacity > 0 && capacity <= MAX_ENTITIES_PER_ARCHETYPE {
-            unsafe {
-                let layout = Layout::from_size_align_unchecked(
-                    size_of::<Entity>() * capacity as usize,
-                    align_of::<Entity>(),
-                );
-                archetype.entity_associations = alloc(layout) as *mut Entity;
-                assert_ne!(archetype.entity_associations, core::ptr::null_mut());
-
-                for (index, component) in archetype.descriptor.components().iter().enumerate() {
-                    let layout = Layout::from_size_align_unchecked(
-                        component.size as usize * capacity as usize,
-                        component.align as usize,
-                    );
-                    archetype.pointers[index] = alloc(layout);
-                    assert_ne!(archetype.pointers[index], core::ptr::null_mut());
-                }
-                archetype.capacity = capacity;
-            }
+            unsafe { archetype.try_set_capacity(capacity)? };
         }
-        archetype
+        Ok(archetype)
+    }
+
+    /// Returns the allocator used for the backing memory of the archetype.
+    pub fn allocator(&self) -> &A {
+        &self.allocator
     }
 
     pub fn descriptor(&self) -> &ArchetypeDescriptor {
@@ -75,7 +111,7 @@ impl Archetype {
     }
 }
 
-impl Drop for Archetype {
+impl<A: ArchetypeAllocator> Drop for Archetype<A> {
     fn drop(&mut self) {
         // Archetype is empty if there is no capacity.
         if self.capacity == 0 {
@@ -10,6 +10,8 @@ fn test_archetype_constructors() {
     // Empty constructor should not panic
     let _ = Archetype::new(descriptor);
     Archetype::with_capacity(descriptor, 0);
+    // Allocating the maximum capacity is infeasible with wide entity handles.
+    #[cfg(not(feature = "wide_entity_handles"))]
     Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE);
     Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE + 1);
 }
@@ -195,3 +197,74 @@ fn test_archetype_read_components() {
         );
     }
 }
+
+#[test]
+fn test_archetype_truncate() {
+    let drops = DropCounter::default();
+    unsafe {
+        let descriptor = <(A, DropCounted) as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype = Archetype::new(descriptor);
+        for i in 0..10 {
+            archetype.push_entity_unchecked(
+                Entity::INVALID,
+                (A { _data: i as usize }, drops.component(i as usize)),
+            );
+        }
+        let capacity = archetype.capacity();
+
+        archetype.truncate(12);
+        assert_eq!(archetype.len(), 10);
+        assert_eq!(drops.count(), 0);
+
+        archetype.truncate(4);
+        assert_eq!(archetype.len(), 4);
+        assert_eq!(archetype.entities().len(), 4);
+        assert_eq!(drops.count(), 6);
+        let slices: (&[A], &[DropCounted]) =
+            archetype.get_slices_unchecked_exact::<(A, DropCounted)>();
+        assert_eq!(slices.0[3], A { _data: 3 });
+        assert_eq!(slices.1[3]._data, 3);
+
+        archetype.clear();
+        assert_eq!(archetype.len(), 0);
+        assert_eq!(archetype.capacity(), capacity);
+        assert_eq!(drops.count(), 10);
+    }
+}
+
+#[test]
+fn test_archetype_reserve_shrink() {
+    unsafe {
+        let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype = Archetype::with_capacity(descriptor, 4);
+        for _ in 0..3 {
+            archetype.push_entity_unchecked(Entity::INVALID, (A::default(), B::default()));
+        }
+        let entity_size = archetype.entity_size();
+        assert!(
+            entity_size
+                >= core::mem::size_of::<Entity>()
+                    + core::mem::size_of::<A>()
+                    + core::mem::size_of::<B>()
+        );
+        assert_eq!(archetype.allocated_bytes(), 4 * entity_size);
+        assert_eq!(archetype.used_bytes(), 3 * entity_size);
+
+        archetype.reserve(2);
+        assert_eq!(archetype.capacity(), 5);
+        archetype.reserve(1);
+        assert_eq!(archetype.capacity(), 5);
+
+        archetype.shrink_to_fit();
+        assert_eq!(archetype.capacity(), 3);
+        let slices: (&[A], &[B]) = archetype.get_slices_unchecked_exact::<(A, B)>();
+        assert_eq!(slices.0, [A::default(), A::default(), A::default()]);
+
+        archetype.clear();
+        archetype.shrink_to_fit();
+        assert_eq!(archetype.capacity(), 1);
+        archetype.push_entity_unchecked(Entity::INVALID, (A::default(), B::default()));
+        archetype.push_entity_unchecked(Entity::INVALID, (A::default(), B::default()));
+        assert_eq!(archetype.len(), 2);
+    }
+}
@@ -0,0 +1,375 @@
+use super::Archetype;
+use crate::allocator::ArchetypeAllocator;
+use crate::descriptors::component_group::ComponentGroup;
+use crate::descriptors::component_type_id::ComponentTypeId;
+#[cfg(feature = "change_detection")]
+use crate::MAX_COMPONENTS_PER_ENTITY;
+#[cfg(feature = "change_detection")]
+use alloc::alloc::Layout;
+#[cfg(feature = "change_detection")]
+use core::mem::{align_of, size_of};
+
+/// Stores the change ticks at which a component was added and at which it was last mutably accessed.
+#[cfg(feature = "change_detection")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComponentTicks {
+    pub added: u32,
+    pub changed: u32,
+}
+
+#[cfg(feature = "change_detection")]
+impl ComponentTicks {
+    /// Returns whether the component was added after [`last_run`], as seen from [`this_run`].
+    /// Ticks are compared relative to [`this_run`], so the comparison remains correct when ticks wrap around.
+    pub const fn is_added(&self, last_run: u32, this_run: u32) -> bool {
+        Self::is_newer(self.added, last_run, this_run)
+    }
+
+    /// Returns whether the component was changed after [`last_run`], as seen from [`this_run`].
+    /// Ticks are compared relative to [`this_run`], so the comparison remains correct when ticks wrap around.
+    pub const fn is_changed(&self, last_run: u32, this_run: u32) -> bool {
+        Self::is_newer(self.changed, last_run, this_run)
+    }
+
+    const fn is_newer(tick: u32, last_run: u32, this_run: u32) -> bool {
+        this_run.wrapping_sub(tick) < this_run.wrapping_sub(last_run)
+    }
+}
+
+/// The ticks of a single component column, used to mark the column as changed once it is mutably accessed.
+/// Stores nothing and marking is a no-op if the `change_detection` feature is disabled.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TickColumn {
+    #[cfg(feature = "change_detection")]
+    pointer: *mut ComponentTicks,
+    #[cfg(feature = "change_detection")]
+    change_tick: u32,
+}
+
+impl TickColumn {
+    /// Marks the first [`len`] components of the column as changed.
+    /// # Safety
+    /// - [`len`] must not exceed the amount of entities in the archetype of the column.
+    /// - The column must not be accessed concurrently.
+    #[cfg(feature = "change_detection")]
+    pub(crate) unsafe fn set_all_changed(&self, len: usize) {
+        if self.pointer.is_null() {
+            return;
+        }
+        for index in 0..len {
+            (*self.pointer.add(index)).changed = self.change_tick;
+        }
+    }
+
+    #[cfg(not(feature = "change_detection"))]
+    pub(crate) unsafe fn set_all_changed(&self, _len: usize) {}
+}
+
+/// Stores the component ticks for each column of an archetype.
+/// Stores nothing and all operations are no-ops if the `change_detection` feature is disabled.
+#[derive(Debug)]
+pub(super) struct TickColumns {
+    #[cfg(feature = "change_detection")]
+    pointers: [*mut ComponentTicks; MAX_COMPONENTS_PER_ENTITY],
+}
+
+#[cfg(feature = "change_detection")]
+impl TickColumns {
+    pub(super) const fn new() -> Self {
+        Self {
+            pointers: [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY],
+        }
+    }
+
+    /// Returns pointers to the tick column pointers, alongside the size and alignment of their elements.
+    pub(super) fn blocks_mut(
+        &mut self,
+        columns: usize,
+    ) -> impl Iterator<Item = super::data_access::MemoryBlock> + '_ {
+        self.pointers[0..columns].iter_mut().map(|pointer| {
+            (
+                pointer as *mut *mut ComponentTicks as *mut *mut u8,
+                size_of::<ComponentTicks>(),
+                align_of::<ComponentTicks>(),
+            )
+        })
+    }
+
+    /// Returns the amount of bytes used by the ticks of a single entity.
+    pub(super) const fn entity_size(columns: usize) -> usize {
+        size_of::<ComponentTicks>() * columns
+    }
+
+    /// Deallocates the tick columns.
+    /// # Safety
+    /// - [`columns`] must match the amount of columns of the archetype.
+    /// - [`capacity`] must match the current capacity of the archetype.
+    pub(super) unsafe fn dealloc<A: ArchetypeAllocator>(
+        &mut self,
+        allocator: &A,
+        columns: usize,
+        capacity: u32,
+    ) {
+        for pointer in self.pointers[0..columns].iter_mut() {
+            if pointer.is_null() {
+                continue;
+            }
+            allocator.dealloc(*pointer as *mut u8, Self::layout(capacity));
+            *pointer = core::ptr::null_mut();
+        }
+    }
+
+    /// Swaps the ticks of the entities at the provided positions.
+    /// # Safety
+    /// - [`first`] and [`second`] must be smaller than the capacity of the archetype.
+    pub(super) unsafe fn swap(&mut self, columns: usize, first: u32, second: u32) {
+        for pointer in self.pointers[0..columns].iter() {
+            core::ptr::swap(pointer.add(first as usize), pointer.add(second as usize));
+        }
+    }
+
+    /// Copies the ticks of a single component between two archetypes.
+    /// # Safety
+    /// - Columns and indices must be in bounds of their respective archetypes.
+    pub(super) unsafe fn copy(
+        source: &Self,
+        source_column: usize,
+        source_index: u32,
+        destination: &mut Self,
+        destination_column: usize,
+        destination_index: u32,
+    ) {
+        *destination.pointers[destination_column].add(destination_index as usize) =
+            *source.pointers[source_column].add(source_index as usize);
+    }
+
+    /// Marks the component at the given position as added, and therefore also as changed.
+    /// # Safety
+    /// - Column and index must be in bounds.
+    /// - The archetype must not be accessed concurrently.
+    pub(super) unsafe fn set_added(&self, column: usize, index: u32, change_tick: u32) {
+        *self.pointers[column].add(index as usize) = ComponentTicks {
+            added: change_tick,
+            changed: change_tick,
+        };
+    }
+
+    /// Marks the component at the given position as changed.
+    /// # Safety
+    /// - Column and index must be in bounds.
+    /// - The archetype must not be accessed concurrently.
+    pub(super) unsafe fn set_changed(&self, column: usize, index: u32, change_tick: u32) {
+        (*self.pointers[column].add(index as usize)).changed = change_tick;
+    }
+
+    /// Marks the first [`len`] components in the given column as changed.
+    /// # Safety
+    /// - Column must be in bounds and len may not exceed the amount of entities.
+    /// - The archetype must not be accessed concurrently.
+    pub(super) unsafe fn set_all_changed(&self, column: usize, len: u32, change_tick: u32) {
+        for index in 0..len as usize {
+            (*self.pointers[column].add(index)).changed = change_tick;
+        }
+    }
+
+    /// Returns the ticks of the component at the given position.
+    /// # Safety
+    /// - Column and index must be in bounds.
+    pub(super) unsafe fn get(&self, column: usize, index: u32) -> ComponentTicks {
+        *self.pointers[column].add(index as usize)
+    }
+
+    unsafe fn layout(capacity: u32) -> Layout {
+        Layout::from_size_align_unchecked(
+            size_of::<ComponentTicks>() * capacity as usize,
+            align_of::<ComponentTicks>(),
+        )
+    }
+}
+
+#[cfg(not(feature = "change_detection"))]
+impl TickColumns {
+    pub(super) const fn new() -> Self {
+        Self {}
+    }
+
+    pub(super) fn blocks_mut(
+        &mut self,
+        _columns: usize,
+    ) -> impl Iterator<Item = super::data_access::MemoryBlock> {
+        core::iter::empty()
+    }
+
+    pub(super) const fn entity_size(_columns: usize) -> usize {
+        0
+    }
+
+    pub(super) unsafe fn dealloc<A: ArchetypeAllocator>(
+        &mut self,
+        _allocator: &A,
+        _columns: usize,
+        _capacity: u32,
+    ) {
+    }
+
+    pub(super) unsafe fn swap(&mut self, _columns: usize, _first: u32, _second: u32) {}
+
+    pub(super) unsafe fn copy(
+        _source: &Self,
+        _source_column: usize,
+        _source_index: u32,
+        _destination: &mut Self,
+        _destination_column: usize,
+        _destination_index: u32,
+    ) {
+    }
+
+    pub(super) unsafe fn set_added(&self, _column: usize, _index: u32, _change_tick: u32) {}
+
+    pub(super) unsafe fn set_changed(&self, _column: usize, _index: u32, _change_tick: u32) {}
+
+    pub(super) unsafe fn set_all_changed(&self, _column: usize, _len: u32, _change_tick: u32) {}
+}
+
+impl<A: ArchetypeAllocator> Archetype<A> {
+    /// Returns the ticks of the component of the given type for the entity at [`index`].
+    /// Returns None if the component type is not present or index is out of bounds.
+    #[cfg(feature = "change_detection")]
+    pub fn component_ticks(
+        &self,
+        component_type_id: ComponentTypeId,
+        index: u32,
+    ) -> Option<ComponentTicks> {
+        if index >= self.len() {
+            return None;
+        }
+        let column = self.column_index(component_type_id)?;
+        unsafe { Some(self.ticks.get(column, index)) }
+    }
+
+    /// Returns the tick column of the given component type, marking components with [`change_tick`].
+    /// Marking the returned column does nothing if the component type is not present in the archetype.
+    pub(crate) fn tick_column(
+        &self,
+        component_type_id: ComponentTypeId,
+        change_tick: u32,
+    ) -> TickColumn {
+        #[cfg(feature = "change_detection")]
+        {
+            TickColumn {
+                pointer: self
+                    .column_index(component_type_id)
+                    .map_or(core::ptr::null_mut(), |column| self.ticks.pointers[column]),
+                change_tick,
+            }
+        }
+        #[cfg(not(feature = "change_detection"))]
+        {
+            let _ = (component_type_id, change_tick);
+            TickColumn {}
+        }
+    }
+
+    /// Marks all components of the entity at [`index`] as added.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_added_unchecked(&self, index: u32, change_tick: u32) {
+        for column in 0..self.descriptor.len() as usize {
+            self.ticks.set_added(column, index, change_tick);
+        }
+    }
+
+    /// Marks the component of the given type of the entity at [`index`] as added.
+    /// Does nothing if the component type is not present in the archetype.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_component_added_unchecked(
+        &self,
+        component_type_id: ComponentTypeId,
+        index: u32,
+        change_tick: u32,
+    ) {
+        if let Some(column) = self.column_index(component_type_id) {
+            self.ticks.set_added(column, index, change_tick);
+        }
+    }
+
+    /// Marks the component of the given type of the entity at [`index`] as changed.
+    /// Does nothing if the component type is not present in the archetype.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_component_changed_unchecked(
+        &self,
+        component_type_id: ComponentTypeId,
+        index: u32,
+        change_tick: u32,
+    ) {
+        if let Some(column) = self.column_index(component_type_id) {
+            self.ticks.set_changed(column, index, change_tick);
+        }
+    }
+
+    /// Marks the components of the given type of all entities as changed.
+    /// Does nothing if the component type is not present in the archetype.
+    /// # Safety
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_column_changed_unchecked(
+        &self,
+        component_type_id: ComponentTypeId,
+        change_tick: u32,
+    ) {
+        if let Some(column) = self.column_index(component_type_id) {
+            self.ticks.set_all_changed(column, self.len(), change_tick);
+        }
+    }
+
+    /// Marks the components in [`G`] of the entity at [`index`] as added.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_fuzzy_components_added_unchecked<G: ComponentGroup>(
+        &self,
+        index: u32,
+        change_tick: u32,
+    ) {
+        for component in G::DESCRIPTOR.archetype().components() {
+            self.mark_component_added_unchecked(component.component_type_id, index, change_tick);
+        }
+    }
+
+    /// Marks the components in [`G`] of the entity at [`index`] as changed.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_fuzzy_components_changed_unchecked<G: ComponentGroup>(
+        &self,
+        index: u32,
+        change_tick: u32,
+    ) {
+        for component in G::DESCRIPTOR.archetype().components() {
+            self.mark_component_changed_unchecked(component.component_type_id, index, change_tick);
+        }
+    }
+
+    /// Marks the components in [`G`] of all entities as changed.
+    /// # Safety
+    /// - The archetype must not be accessed concurrently.
+    pub(crate) unsafe fn mark_fuzzy_columns_changed_unchecked<G: ComponentGroup>(
+        &self,
+        change_tick: u32,
+    ) {
+        for component in G::DESCRIPTOR.archetype().components() {
+            self.mark_column_changed_unchecked(component.component_type_id, change_tick);
+        }
+    }
+
+    fn column_index(&self, component_type_id: ComponentTypeId) -> Option<usize> {
+        self.descriptor
+            .components()
+            .binary_search_by_key(&component_type_id, |e| e.component_type_id)
+            .ok()
+    }
+}
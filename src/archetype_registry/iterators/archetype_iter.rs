@@ -4,18 +4,18 @@ use alloc::vec::*;
 use core::iter::FusedIterator;
 use core::marker::PhantomData;
 
-pub(crate) struct ArchetypeIter<'a, G: ComponentGroup> {
+pub(crate) struct ArchetypeIter<'a, A: ArchetypeAllocator, G: ComponentGroup> {
     sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-    archetypes: &'a [Archetype],
+    archetypes: &'a [Archetype<A>],
     current_level: u8,
     current_index_in_level: usize,
     _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup> ArchetypeIter<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ArchetypeIter<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
     ) -> Self {
         Self {
             sorted_mappings,
@@ -27,8 +27,8 @@ impl<'a, G: ComponentGroup> ArchetypeIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for ArchetypeIter<'a, G> {
-    type Item = &'a Archetype;
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for ArchetypeIter<'a, A, G> {
+    type Item = &'a Archetype<A>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if !G::DESCRIPTOR.is_valid() {
@@ -60,4 +60,4 @@ impl<'a, G: ComponentGroup> Iterator for ArchetypeIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for ArchetypeIter<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for ArchetypeIter<'a, A, G> {}
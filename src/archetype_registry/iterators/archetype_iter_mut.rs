@@ -4,18 +4,18 @@ use alloc::vec::*;
 use core::iter::FusedIterator;
 use core::marker::PhantomData;
 
-pub(crate) struct ArchetypeIterMut<'a, G: ComponentGroup> {
+pub(crate) struct ArchetypeIterMut<'a, A: ArchetypeAllocator, G: ComponentGroup> {
     sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-    archetypes: &'a mut [Archetype],
+    archetypes: &'a mut [Archetype<A>],
     current_level: u8,
     current_index_in_level: usize,
     _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup> ArchetypeIterMut<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ArchetypeIterMut<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
     ) -> Self {
         Self {
             sorted_mappings,
@@ -27,8 +27,8 @@ impl<'a, G: ComponentGroup> ArchetypeIterMut<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for ArchetypeIterMut<'a, G> {
-    type Item = &'a mut Archetype;
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for ArchetypeIterMut<'a, A, G> {
+    type Item = &'a mut Archetype<A>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if !G::DESCRIPTOR.is_valid() {
@@ -46,7 +46,7 @@ impl<'a, G: ComponentGroup> Iterator for ArchetypeIterMut<'a, G> {
                     self.current_index_in_level += 1;
                     // Safety: The problem is that the compiler cannot guarantee we don't mutably borrow
                     // the same element twice. We don't, so use unsafe to implement this.
-                    let archetype: &mut Archetype =
+                    let archetype: &mut Archetype<A> =
                         &mut *self.archetypes.as_mut_ptr().offset(arch_index as isize);
                     if archetype
                         .descriptor()
@@ -63,4 +63,4 @@ impl<'a, G: ComponentGroup> Iterator for ArchetypeIterMut<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for ArchetypeIterMut<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for ArchetypeIterMut<'a, A, G> {}
@@ -0,0 +1,62 @@
+use super::super::*;
+use super::*;
+use crate::descriptors::component_group::ComponentGroup;
+use crate::Entity;
+use alloc::vec::*;
+use core::iter::FusedIterator;
+
+pub(crate) struct ComponentIter<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: EntityMatchingIter<'a, A, G>,
+    entities: &'a [Entity],
+    slices: G::SliceRefTuple<'a>,
+    current_index: usize,
+    remaining: usize,
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ComponentIter<'a, A, G> {
+    pub(in crate::archetype_registry) fn new(
+        sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
+        archetypes: &'a [Archetype<A>],
+    ) -> Self {
+        let remaining = ArchetypeIter::<A, G>::new(sorted_mappings, archetypes)
+            .map(|archetype| archetype.len() as usize)
+            .sum();
+        Self {
+            inner_iterator: EntityMatchingIter::new(sorted_mappings, archetypes),
+            entities: &[],
+            slices: G::empty_slice(),
+            current_index: 0,
+            remaining,
+        }
+    }
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for ComponentIter<'a, A, G> {
+    type Item = (Entity, G::RefTuple<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_index >= self.entities.len() {
+            let (entities, slices) = self.inner_iterator.next()?;
+            self.entities = entities;
+            self.slices = slices;
+            self.current_index = 0;
+        }
+        let index = self.current_index;
+        self.current_index += 1;
+        self.remaining -= 1;
+        unsafe {
+            Some((
+                *self.entities.get_unchecked(index),
+                G::slice_get_unchecked(&self.slices, index),
+            ))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ExactSizeIterator for ComponentIter<'a, A, G> {}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for ComponentIter<'a, A, G> {}
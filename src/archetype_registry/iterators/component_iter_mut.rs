@@ -0,0 +1,67 @@
+use super::super::*;
+use super::*;
+use crate::descriptors::component_group::ComponentGroup;
+use crate::Entity;
+use alloc::vec::*;
+use core::iter::FusedIterator;
+
+pub(crate) struct ComponentIterMut<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: EntityMatchingIterMut<'a, A, G>,
+    entities: &'a [Entity],
+    slices: G::SliceMutRefTuple<'a>,
+    current_index: usize,
+    remaining: usize,
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ComponentIterMut<'a, A, G> {
+    pub(in crate::archetype_registry) fn new(
+        sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
+        archetypes: &'a mut [Archetype<A>],
+        change_tick: u32,
+    ) -> Self {
+        let remaining = ArchetypeIter::<A, G>::new(sorted_mappings, archetypes)
+            .map(|archetype| archetype.len() as usize)
+            .sum();
+        Self {
+            inner_iterator: EntityMatchingIterMut::new(sorted_mappings, archetypes, change_tick),
+            entities: &[],
+            slices: G::empty_slice_mut(),
+            current_index: 0,
+            remaining,
+        }
+    }
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for ComponentIterMut<'a, A, G> {
+    type Item = (Entity, G::MutRefTuple<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_index >= self.entities.len() {
+            let (entities, slices) = self.inner_iterator.next()?;
+            self.entities = entities;
+            self.slices = slices;
+            self.current_index = 0;
+        }
+        let index = self.current_index;
+        self.current_index += 1;
+        self.remaining -= 1;
+        // Each index is visited exactly once, so the returned references never alias.
+        unsafe {
+            Some((
+                *self.entities.get_unchecked(index),
+                G::slice_get_unchecked_mut(&mut self.slices, index),
+            ))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> ExactSizeIterator
+    for ComponentIterMut<'a, A, G>
+{
+}
+
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for ComponentIterMut<'a, A, G> {}
@@ -1,44 +1,48 @@
 use super::super::*;
-use crate::descriptors::component_group::ComponentGroup;
 use alloc::vec::*;
 use core::iter::FusedIterator;
-use core::marker::PhantomData;
 
-pub(crate) struct FilterArchetypeIter<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> {
+pub(crate) struct FilterArchetypeIter<
+    'a,
+    A: ArchetypeAllocator,
+    F: Fn(&ArchetypeDescriptor) -> bool,
+> {
     sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-    archetypes: &'a [Archetype],
+    archetypes: &'a [Archetype<A>],
     current_level: u8,
     current_index_in_level: usize,
+    descriptor: &'a ArchetypeDescriptor,
     filter_closure: F,
-    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FilterArchetypeIter<'a, G, F> {
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool> FilterArchetypeIter<'a, A, F> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
+        descriptor: &'a ArchetypeDescriptor,
         filter_closure: F,
     ) -> Self {
         Self {
             sorted_mappings,
             archetypes,
-            current_level: G::DESCRIPTOR.len() - 1,
+            // An invalid descriptor matches nothing, so skip all levels.
+            current_level: match descriptor.is_valid() {
+                true => descriptor.len() - 1,
+                false => MAX_COMPONENTS_PER_ENTITY as u8,
+            },
             current_index_in_level: 0,
-            _phantom: Default::default(),
+            descriptor,
             filter_closure,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterArchetypeIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterArchetypeIter<'a, A, F>
 {
-    type Item = &'a Archetype;
+    type Item = &'a Archetype<A>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if !G::DESCRIPTOR.is_valid() {
-            return None;
-        }
         while (self.current_level as usize) < MAX_COMPONENTS_PER_ENTITY {
             unsafe {
                 let level = &self
@@ -50,9 +54,7 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
                         .archetype_index;
                     self.current_index_in_level += 1;
                     let archetype = &self.archetypes.get_unchecked(arch_index as usize);
-                    if archetype
-                        .descriptor()
-                        .contains_subset(G::DESCRIPTOR.archetype())
+                    if archetype.descriptor().contains_subset(self.descriptor)
                         && (self.filter_closure)(archetype.descriptor())
                     {
                         return Some(archetype);
@@ -66,7 +68,7 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterArchetypeIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
+    for FilterArchetypeIter<'a, A, F>
 {
 }
@@ -1,45 +1,50 @@
 use super::super::*;
-use crate::descriptors::component_group::ComponentGroup;
 use alloc::vec::*;
 use core::iter::FusedIterator;
-use core::marker::PhantomData;
 
-pub(crate) struct FilterArchetypeIterMut<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
-{
+pub(crate) struct FilterArchetypeIterMut<
+    'a,
+    A: ArchetypeAllocator,
+    F: Fn(&ArchetypeDescriptor) -> bool,
+> {
     sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-    archetypes: &'a mut [Archetype],
+    archetypes: &'a mut [Archetype<A>],
     current_level: u8,
     current_index_in_level: usize,
+    descriptor: &'a ArchetypeDescriptor,
     filter_closure: F,
-    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FilterArchetypeIterMut<'a, G, F> {
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool>
+    FilterArchetypeIterMut<'a, A, F>
+{
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
+        descriptor: &'a ArchetypeDescriptor,
         filter_closure: F,
     ) -> Self {
         Self {
             sorted_mappings,
             archetypes,
-            current_level: G::DESCRIPTOR.len() - 1,
+            // An invalid descriptor matches nothing, so skip all levels.
+            current_level: match descriptor.is_valid() {
+                true => descriptor.len() - 1,
+                false => MAX_COMPONENTS_PER_ENTITY as u8,
+            },
             current_index_in_level: 0,
-            _phantom: Default::default(),
+            descriptor,
             filter_closure,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterArchetypeIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterArchetypeIterMut<'a, A, F>
 {
-    type Item = &'a mut Archetype;
+    type Item = &'a mut Archetype<A>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if !G::DESCRIPTOR.is_valid() {
-            return None;
-        }
         unsafe {
             while (self.current_level as usize) < MAX_COMPONENTS_PER_ENTITY {
                 let level = &self
@@ -52,11 +57,9 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
                     self.current_index_in_level += 1;
                     // Safety: The problem is that the compiler cannot guarantee we don't mutably borrow
                     // the same element twice. We don't, so use unsafe to implement this.
-                    let archetype: &mut Archetype =
+                    let archetype: &mut Archetype<A> =
                         &mut *self.archetypes.as_mut_ptr().offset(arch_index as isize);
-                    if archetype
-                        .descriptor()
-                        .contains_subset(G::DESCRIPTOR.archetype())
+                    if archetype.descriptor().contains_subset(self.descriptor)
                         && (self.filter_closure)(archetype.descriptor())
                     {
                         return Some(archetype);
@@ -70,7 +73,7 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterArchetypeIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
+    for FilterArchetypeIterMut<'a, A, F>
 {
 }
@@ -4,25 +4,40 @@ use crate::descriptors::component_group::ComponentGroup;
 use crate::Entity;
 use alloc::vec::*;
 use core::iter::FusedIterator;
+use core::marker::PhantomData;
 
-pub(crate) struct FilterMatchingIter<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> {
-    inner_iterator: FilterArchetypeIter<'a, G, F>,
+pub(crate) struct FilterMatchingIter<
+    'a,
+    A: ArchetypeAllocator,
+    G: ComponentGroup,
+    F: Fn(&ArchetypeDescriptor) -> bool,
+> {
+    inner_iterator: FilterArchetypeIter<'a, A, F>,
+    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FilterMatchingIter<'a, G, F> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FilterMatchingIter<'a, A, G, F>
+{
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
         filter_closure: F,
     ) -> Self {
         Self {
-            inner_iterator: FilterArchetypeIter::new(sorted_mappings, archetypes, filter_closure),
+            inner_iterator: FilterArchetypeIter::new(
+                sorted_mappings,
+                archetypes,
+                G::DESCRIPTOR.archetype(),
+                filter_closure,
+            ),
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterMatchingIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterMatchingIter<'a, A, G, F>
 {
     type Item = G::SliceRefTuple<'a>;
 
@@ -32,35 +47,43 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterMatchingIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FusedIterator for FilterMatchingIter<'a, A, G, F>
 {
 }
 
 pub(crate) struct FilterEntityMatchingIter<
     'a,
+    A: ArchetypeAllocator,
     G: ComponentGroup,
     F: Fn(&ArchetypeDescriptor) -> bool,
 > {
-    inner_iterator: FilterArchetypeIter<'a, G, F>,
+    inner_iterator: FilterArchetypeIter<'a, A, F>,
+    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
-    FilterEntityMatchingIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FilterEntityMatchingIter<'a, A, G, F>
 {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
         filter_closure: F,
     ) -> Self {
         Self {
-            inner_iterator: FilterArchetypeIter::new(sorted_mappings, archetypes, filter_closure),
+            inner_iterator: FilterArchetypeIter::new(
+                sorted_mappings,
+                archetypes,
+                G::DESCRIPTOR.archetype(),
+                filter_closure,
+            ),
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterEntityMatchingIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterEntityMatchingIter<'a, A, G, F>
 {
     type Item = (&'a [Entity], G::SliceRefTuple<'a>);
 
@@ -70,7 +93,7 @@ impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterEntityMatchingIter<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FusedIterator for FilterEntityMatchingIter<'a, A, G, F>
 {
 }
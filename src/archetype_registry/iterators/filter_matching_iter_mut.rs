@@ -4,82 +4,108 @@ use crate::descriptors::component_group::ComponentGroup;
 use crate::Entity;
 use alloc::vec::*;
 use core::iter::FusedIterator;
+use core::marker::PhantomData;
 
-pub(crate) struct FilterMatchingIterMut<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
-{
-    inner_iterator: FilterArchetypeIterMut<'a, G, F>,
+pub(crate) struct FilterMatchingIterMut<
+    'a,
+    A: ArchetypeAllocator,
+    G: ComponentGroup,
+    F: Fn(&ArchetypeDescriptor) -> bool,
+> {
+    inner_iterator: FilterArchetypeIterMut<'a, A, F>,
+    change_tick: u32,
+    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FilterMatchingIterMut<'a, G, F> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FilterMatchingIterMut<'a, A, G, F>
+{
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
         filter_closure: F,
+        change_tick: u32,
     ) -> Self {
         Self {
             inner_iterator: FilterArchetypeIterMut::new(
                 sorted_mappings,
                 archetypes,
+                G::DESCRIPTOR.archetype(),
                 filter_closure,
             ),
+            change_tick,
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterMatchingIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterMatchingIterMut<'a, A, G, F>
 {
     type Item = G::SliceMutRefTuple<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let archetype = self.inner_iterator.next()?;
-        unsafe { Some(archetype.get_fuzzy_slices_unchecked_mut::<G>()) }
+        unsafe {
+            archetype.mark_fuzzy_columns_changed_unchecked::<G>(self.change_tick);
+            Some(archetype.get_fuzzy_slices_unchecked_mut::<G>())
+        }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterMatchingIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FusedIterator for FilterMatchingIterMut<'a, A, G, F>
 {
 }
 
 pub(crate) struct FilterEntityMatchingIterMut<
     'a,
+    A: ArchetypeAllocator,
     G: ComponentGroup,
     F: Fn(&ArchetypeDescriptor) -> bool,
 > {
-    inner_iterator: FilterArchetypeIterMut<'a, G, F>,
+    inner_iterator: FilterArchetypeIterMut<'a, A, F>,
+    change_tick: u32,
+    _phantom: PhantomData<fn(G)>,
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
-    FilterEntityMatchingIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FilterEntityMatchingIterMut<'a, A, G, F>
 {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
         filter_closure: F,
+        change_tick: u32,
     ) -> Self {
         Self {
             inner_iterator: FilterArchetypeIterMut::new(
                 sorted_mappings,
                 archetypes,
+                G::DESCRIPTOR.archetype(),
                 filter_closure,
             ),
+            change_tick,
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
-    for FilterEntityMatchingIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> Iterator
+    for FilterEntityMatchingIterMut<'a, A, G, F>
 {
     type Item = (&'a [Entity], G::SliceMutRefTuple<'a>);
 
     fn next(&mut self) -> Option<Self::Item> {
         let archetype = self.inner_iterator.next()?;
-        unsafe { Some(archetype.get_entity_fuzzy_slices_unchecked_mut::<G>()) }
+        unsafe {
+            archetype.mark_fuzzy_columns_changed_unchecked::<G>(self.change_tick);
+            Some(archetype.get_entity_fuzzy_slices_unchecked_mut::<G>())
+        }
     }
 }
 
-impl<'a, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool> FusedIterator
-    for FilterEntityMatchingIterMut<'a, G, F>
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>
+    FusedIterator for FilterEntityMatchingIterMut<'a, A, G, F>
 {
 }
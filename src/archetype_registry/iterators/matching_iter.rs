@@ -5,14 +5,14 @@ use crate::Entity;
 use alloc::vec::*;
 use core::iter::FusedIterator;
 
-pub(crate) struct MatchingIter<'a, G: ComponentGroup> {
-    inner_iterator: ArchetypeIter<'a, G>,
+pub(crate) struct MatchingIter<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: ArchetypeIter<'a, A, G>,
 }
 
-impl<'a, G: ComponentGroup> MatchingIter<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> MatchingIter<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
     ) -> Self {
         Self {
             inner_iterator: ArchetypeIter::new(sorted_mappings, archetypes),
@@ -20,7 +20,7 @@ impl<'a, G: ComponentGroup> MatchingIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for MatchingIter<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for MatchingIter<'a, A, G> {
     type Item = G::SliceRefTuple<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -29,16 +29,16 @@ impl<'a, G: ComponentGroup> Iterator for MatchingIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for MatchingIter<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for MatchingIter<'a, A, G> {}
 
-pub(crate) struct EntityMatchingIter<'a, G: ComponentGroup> {
-    inner_iterator: ArchetypeIter<'a, G>,
+pub(crate) struct EntityMatchingIter<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: ArchetypeIter<'a, A, G>,
 }
 
-impl<'a, G: ComponentGroup> EntityMatchingIter<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> EntityMatchingIter<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a [Archetype],
+        archetypes: &'a [Archetype<A>],
     ) -> Self {
         Self {
             inner_iterator: ArchetypeIter::new(sorted_mappings, archetypes),
@@ -46,7 +46,7 @@ impl<'a, G: ComponentGroup> EntityMatchingIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for EntityMatchingIter<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for EntityMatchingIter<'a, A, G> {
     type Item = (&'a [Entity], G::SliceRefTuple<'a>);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -55,4 +55,4 @@ impl<'a, G: ComponentGroup> Iterator for EntityMatchingIter<'a, G> {
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for EntityMatchingIter<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for EntityMatchingIter<'a, A, G> {}
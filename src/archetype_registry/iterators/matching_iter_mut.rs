@@ -5,54 +5,69 @@ use crate::Entity;
 use alloc::vec::*;
 use core::iter::FusedIterator;
 
-pub(crate) struct MatchingIterMut<'a, G: ComponentGroup> {
-    inner_iterator: ArchetypeIterMut<'a, G>,
+pub(crate) struct MatchingIterMut<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: ArchetypeIterMut<'a, A, G>,
+    change_tick: u32,
 }
 
-impl<'a, G: ComponentGroup> MatchingIterMut<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> MatchingIterMut<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
+        change_tick: u32,
     ) -> Self {
         Self {
             inner_iterator: ArchetypeIterMut::new(sorted_mappings, archetypes),
+            change_tick,
         }
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for MatchingIterMut<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for MatchingIterMut<'a, A, G> {
     type Item = G::SliceMutRefTuple<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let archetype = self.inner_iterator.next()?;
-        unsafe { Some(archetype.get_fuzzy_slices_unchecked_mut::<G>()) }
+        unsafe {
+            archetype.mark_fuzzy_columns_changed_unchecked::<G>(self.change_tick);
+            Some(archetype.get_fuzzy_slices_unchecked_mut::<G>())
+        }
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for MatchingIterMut<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator for MatchingIterMut<'a, A, G> {}
 
-pub(crate) struct EntityMatchingIterMut<'a, G: ComponentGroup> {
-    inner_iterator: ArchetypeIterMut<'a, G>,
+pub(crate) struct EntityMatchingIterMut<'a, A: ArchetypeAllocator, G: ComponentGroup> {
+    inner_iterator: ArchetypeIterMut<'a, A, G>,
+    change_tick: u32,
 }
 
-impl<'a, G: ComponentGroup> EntityMatchingIterMut<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> EntityMatchingIterMut<'a, A, G> {
     pub(in crate::archetype_registry) fn new(
         sorted_mappings: &'a [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-        archetypes: &'a mut [Archetype],
+        archetypes: &'a mut [Archetype<A>],
+        change_tick: u32,
     ) -> Self {
         Self {
             inner_iterator: ArchetypeIterMut::new(sorted_mappings, archetypes),
+            change_tick,
         }
     }
 }
 
-impl<'a, G: ComponentGroup> Iterator for EntityMatchingIterMut<'a, G> {
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> Iterator for EntityMatchingIterMut<'a, A, G> {
     type Item = (&'a [Entity], G::SliceMutRefTuple<'a>);
 
     fn next(&mut self) -> Option<Self::Item> {
         let archetype = self.inner_iterator.next()?;
-        unsafe { Some(archetype.get_entity_fuzzy_slices_unchecked_mut::<G>()) }
+        unsafe {
+            archetype.mark_fuzzy_columns_changed_unchecked::<G>(self.change_tick);
+            Some(archetype.get_entity_fuzzy_slices_unchecked_mut::<G>())
+        }
     }
 }
 
-impl<'a, G: ComponentGroup> FusedIterator for EntityMatchingIterMut<'a, G> {}
+impl<'a, A: ArchetypeAllocator, G: ComponentGroup> FusedIterator
+    for EntityMatchingIterMut<'a, A, G>
+{
+}
@@ -4,6 +4,9 @@ mod matching_iter_mut;
 mod archetype_iter;
 mod archetype_iter_mut;
 
+mod component_iter;
+mod component_iter_mut;
+
 mod filter_archetype_iter;
 mod filter_archetype_iter_mut;
 mod filter_matching_iter;
@@ -11,6 +14,8 @@ mod filter_matching_iter_mut;
 
 pub(crate) use archetype_iter::*;
 pub(crate) use archetype_iter_mut::*;
+pub(crate) use component_iter::*;
+pub(crate) use component_iter_mut::*;
 pub(crate) use filter_archetype_iter::*;
 pub(crate) use filter_archetype_iter_mut::*;
 pub(crate) use filter_matching_iter::*;
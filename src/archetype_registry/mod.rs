@@ -6,14 +6,21 @@ mod tests;
 
 use alloc::vec::*;
 use core::ops::{Index, IndexMut};
+use core::sync::atomic::{AtomicU32, Ordering};
 use sorted_archetype_key::*;
 
+use crate::allocator::{ArchetypeAllocator, Global};
 use crate::archetype::Archetype;
 use crate::archetype_registry::iterators::*;
 use crate::constants::*;
 use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
 use crate::descriptors::component_descriptor::ComponentDescriptor;
 use crate::descriptors::component_group::ComponentGroup;
+use crate::descriptors::component_type_id::ComponentTypeId;
+use crate::query::{
+    ArchetypeFilter, QueryChunkIter, QueryFetch, QueryFilter, QueryIter, ReadOnlyQueryFetch,
+};
+use crate::registry::RegistryErrorKind;
 use crate::Entity;
 
 const DEFAULT_VECTOR_CAPACITY: usize = 64;
@@ -37,14 +44,22 @@ fn disjoint_mut<T>(slice: &mut [T], first: usize, second: usize) -> Option<(&mut
 
 #[derive(Debug)]
 /// Stores all archetypes.
-pub struct ArchetypeRegistry {
-    // TODO: Currently not a great approach, should become a graph
+pub struct ArchetypeRegistry<A: ArchetypeAllocator = Global> {
     sorted_mappings: [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
-    archetypes: Vec<Archetype>,
+    archetypes: Vec<Archetype<A>>,
+    change_tick: AtomicU32,
+    allocator: A,
 }
 
 impl Default for ArchetypeRegistry {
     fn default() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<A: ArchetypeAllocator> ArchetypeRegistry<A> {
+    /// Creates an archetype registry which allocates the memory of its archetypes using the given allocator.
+    pub fn new_in(allocator: A) -> Self {
         Self {
             sorted_mappings: [
                 Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
@@ -62,25 +77,56 @@ impl Default for ArchetypeRegistry {
                 Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
                 Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
             ],
-            archetypes: Vec::with_capacity(128),
+            archetypes: {
+                let mut archetypes = Vec::with_capacity(128);
+                archetypes.push(Archetype::new_in(
+                    &ArchetypeDescriptor::EMPTY,
+                    allocator.clone(),
+                ));
+                archetypes
+            },
+            change_tick: AtomicU32::new(0),
+            allocator,
         }
     }
-}
 
-impl ArchetypeRegistry {
+    /// Index of the root archetype, which contains no components and always exists.
+    pub const EMPTY_ARCHETYPE_INDEX: u16 = 0;
+
+    /// Returns the change tick with which added and changed components are marked.
+    pub(crate) fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Relaxed)
+    }
+
+    /// Increments the change tick, wrapping around on overflow.
+    #[cfg(feature = "change_detection")]
+    pub(crate) fn increment_change_tick(&mut self) {
+        let change_tick = self.change_tick.get_mut();
+        *change_tick = change_tick.wrapping_add(1);
+    }
+
+    /// Increments the change tick for a run of a query, wrapping around on overflow.
+    /// Returns the change tick of the run, which is the change tick before incrementing.
+    /// Changes made after the run are therefore marked with a later tick than the run itself.
+    #[cfg(feature = "change_detection")]
+    pub(crate) fn advance_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::Relaxed)
+    }
+
     #[allow(dead_code)]
-    pub fn find_archetype(&self, archetype_descriptor: &ArchetypeDescriptor) -> Option<&Archetype> {
+    pub fn find_archetype(
+        &self,
+        archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Option<&Archetype<A>> {
         let len = archetype_descriptor.len() as usize;
         if len > MAX_COMPONENTS_PER_ENTITY || !archetype_descriptor.is_valid() {
             return None;
         }
-        match self.sorted_mappings[len - 1]
-            .binary_search_by_key(&archetype_descriptor.archetype_id(), |e| e.id)
-        {
-            Ok(found_index) => Some(
-                &self.archetypes
-                    [self.sorted_mappings[len - 1][found_index].archetype_index as usize],
-            ),
+        if len == 0 {
+            return Some(&self.archetypes[Self::EMPTY_ARCHETYPE_INDEX as usize]);
+        }
+        match self.search_archetype(archetype_descriptor) {
+            Ok(archetype_index) => Some(&self.archetypes[archetype_index as usize]),
             Err(_) => None,
         }
     }
@@ -89,37 +135,174 @@ impl ArchetypeRegistry {
     pub fn find_archetype_mut(
         &mut self,
         archetype_descriptor: &ArchetypeDescriptor,
-    ) -> Option<&mut Archetype> {
+    ) -> Option<&mut Archetype<A>> {
         let len = archetype_descriptor.len() as usize;
         if len > MAX_COMPONENTS_PER_ENTITY || !archetype_descriptor.is_valid() {
             return None;
         }
-        match self.sorted_mappings[len - 1]
-            .binary_search_by_key(&archetype_descriptor.archetype_id(), |e| e.id)
-        {
-            Ok(found_index) => Some(
-                &mut self.archetypes
-                    [self.sorted_mappings[len - 1][found_index].archetype_index as usize],
-            ),
+        if len == 0 {
+            return Some(&mut self.archetypes[Self::EMPTY_ARCHETYPE_INDEX as usize]);
+        }
+        match self.search_archetype(archetype_descriptor) {
+            Ok(archetype_index) => Some(&mut self.archetypes[archetype_index as usize]),
             Err(_) => None,
         }
     }
 
     /// Returns mutable reference to source archetype and finds or creates a new archetype by adding
     /// the given component type as defined by component descriptor.
+    /// Follows the cached edge of the source archetype if present, otherwise the edge is created.
     pub fn find_or_create_archetype_adding_component(
         &mut self,
         source_archetype_index: u16,
         component_descriptor: &ComponentDescriptor,
-    ) -> Option<(&mut Archetype, u16, &mut Archetype)> {
+    ) -> Option<(&mut Archetype<A>, u16, &mut Archetype<A>)> {
+        match self.try_find_or_create_archetype_adding_component(
+            source_archetype_index,
+            component_descriptor,
+        ) {
+            Ok(v) => Some(v),
+            Err(RegistryErrorKind::AllocationFailed) => panic!("Archetype allocation failed."),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns mutable reference to source archetype and finds or creates a new archetype by adding
+    /// the given component type as defined by component descriptor.
+    /// Follows the cached edge of the source archetype if present, otherwise the edge is created.
+    /// Returns the reason of failure instead of panicking if the new archetype could not be allocated.
+    pub fn try_find_or_create_archetype_adding_component(
+        &mut self,
+        source_archetype_index: u16,
+        component_descriptor: &ComponentDescriptor,
+    ) -> Result<(&mut Archetype<A>, u16, &mut Archetype<A>), RegistryErrorKind> {
         // Range check
-        if source_archetype_index as usize > self.archetypes.len() {
+        if source_archetype_index as usize >= self.archetypes.len() {
+            return Err(RegistryErrorKind::InvalidEntity);
+        }
+        let component_type_id = component_descriptor.component_type_id();
+        let destination_archetype_index = match self.archetypes[source_archetype_index as usize]
+            .add_edge(component_type_id)
+        {
+            Some(v) => v,
+            None => {
+                // create new archetype
+                let source_descriptor =
+                    self.archetypes[source_archetype_index as usize].descriptor();
+                let new_archetype_descriptor =
+                    match source_descriptor.add_component(component_descriptor) {
+                        Some(v) => v,
+                        None if source_descriptor.len() as usize == MAX_COMPONENTS_PER_ENTITY => {
+                            return Err(RegistryErrorKind::LimitExceeded)
+                        }
+                        None => return Err(RegistryErrorKind::InvalidComponentGroup),
+                    };
+
+                let (destination_archetype_index, _) =
+                    self.try_find_or_create_archetype(&new_archetype_descriptor)?;
+                self.link_archetypes(
+                    source_archetype_index,
+                    destination_archetype_index,
+                    component_type_id,
+                )?;
+                destination_archetype_index
+            }
+        };
+
+        let (source, destination) = disjoint_mut(
+            &mut self.archetypes,
+            source_archetype_index as usize,
+            destination_archetype_index as usize,
+        )
+        .ok_or(RegistryErrorKind::InvalidComponentGroup)?;
+
+        Ok((source, destination_archetype_index, destination))
+    }
+
+    /// Returns mutable reference to source archetype and finds or creates a new archetype by removing
+    /// the given component type as defined by component descriptor.
+    /// Follows the cached edge of the source archetype if present, otherwise the edge is created.
+    pub fn find_or_create_archetype_removing_component(
+        &mut self,
+        source_archetype_index: u16,
+        component_descriptor: &ComponentDescriptor,
+    ) -> Option<(&mut Archetype<A>, u16, &mut Archetype<A>)> {
+        match self.try_find_or_create_archetype_removing_component(
+            source_archetype_index,
+            component_descriptor,
+        ) {
+            Ok(v) => Some(v),
+            Err(RegistryErrorKind::AllocationFailed) => panic!("Archetype allocation failed."),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns mutable reference to source archetype and finds or creates a new archetype by removing
+    /// the given component type as defined by component descriptor.
+    /// Follows the cached edge of the source archetype if present, otherwise the edge is created.
+    /// Returns the reason of failure instead of panicking if the new archetype could not be allocated.
+    pub fn try_find_or_create_archetype_removing_component(
+        &mut self,
+        source_archetype_index: u16,
+        component_descriptor: &ComponentDescriptor,
+    ) -> Result<(&mut Archetype<A>, u16, &mut Archetype<A>), RegistryErrorKind> {
+        // Range check
+        if source_archetype_index as usize >= self.archetypes.len() {
+            return Err(RegistryErrorKind::InvalidEntity);
+        }
+        let component_type_id = component_descriptor.component_type_id();
+        let destination_archetype_index =
+            match self.archetypes[source_archetype_index as usize].remove_edge(component_type_id) {
+                Some(v) => v,
+                None => {
+                    // create new archetype
+                    let new_archetype_descriptor = self.archetypes[source_archetype_index as usize]
+                        .descriptor()
+                        .remove_component(component_type_id)
+                        .ok_or(RegistryErrorKind::InvalidComponentGroup)?;
+
+                    let (destination_archetype_index, _) =
+                        self.try_find_or_create_archetype(&new_archetype_descriptor)?;
+                    self.link_archetypes(
+                        destination_archetype_index,
+                        source_archetype_index,
+                        component_type_id,
+                    )?;
+                    destination_archetype_index
+                }
+            };
+
+        let (source, destination) = disjoint_mut(
+            &mut self.archetypes,
+            source_archetype_index as usize,
+            destination_archetype_index as usize,
+        )
+        .ok_or(RegistryErrorKind::InvalidComponentGroup)?;
+
+        Ok((source, destination_archetype_index, destination))
+    }
+
+    /// Returns mutable reference to source archetype and finds or creates a new archetype by adding
+    /// all component types of the given archetype descriptor at once.
+    pub fn find_or_create_archetype_adding_components(
+        &mut self,
+        source_archetype_index: u16,
+        archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Option<(&mut Archetype<A>, u16, &mut Archetype<A>)> {
+        if archetype_descriptor.len() == 1 {
+            return self.find_or_create_archetype_adding_component(
+                source_archetype_index,
+                &archetype_descriptor.components()[0],
+            );
+        }
+        // Range check
+        if source_archetype_index as usize >= self.archetypes.len() {
             return None;
         }
         // create new archetype
         let new_archetype_descriptor = self.archetypes[source_archetype_index as usize]
             .descriptor()
-            .add_component(component_descriptor)?;
+            .add_components(archetype_descriptor)?;
 
         let (destination_archetype_index, _) =
             self.find_or_create_archetype(&new_archetype_descriptor)?;
@@ -129,26 +312,30 @@ impl ArchetypeRegistry {
             source_archetype_index as usize,
             destination_archetype_index as usize,
         )?;
-
         Some((source, destination_archetype_index, destination))
     }
 
     /// Returns mutable reference to source archetype and finds or creates a new archetype by removing
-    /// the given component type as defined by component descriptor.
-    pub fn find_or_create_archetype_removing_component(
+    /// all component types of the given archetype descriptor at once.
+    pub fn find_or_create_archetype_removing_components(
         &mut self,
         source_archetype_index: u16,
-        component_descriptor: &ComponentDescriptor,
-    ) -> Option<(&mut Archetype, u16, &mut Archetype)> {
+        archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Option<(&mut Archetype<A>, u16, &mut Archetype<A>)> {
+        if archetype_descriptor.len() == 1 {
+            return self.find_or_create_archetype_removing_component(
+                source_archetype_index,
+                &archetype_descriptor.components()[0],
+            );
+        }
         // Range check
-        if source_archetype_index as usize > self.archetypes.len() {
+        if source_archetype_index as usize >= self.archetypes.len() {
             return None;
         }
-
         // create new archetype
         let new_archetype_descriptor = self.archetypes[source_archetype_index as usize]
             .descriptor()
-            .remove_component(component_descriptor.component_type_id())?;
+            .remove_components(archetype_descriptor)?;
 
         let (destination_archetype_index, _) =
             self.find_or_create_archetype(&new_archetype_descriptor)?;
@@ -161,38 +348,106 @@ impl ArchetypeRegistry {
         Some((source, destination_archetype_index, destination))
     }
 
+    /// Searches the archetype matching the given non-empty archetype descriptor.
+    /// Archetype ids are hashes, so the components of all archetypes sharing the id are compared as well.
+    /// Returns the index of the archetype if found, otherwise the position at which its key should be inserted.
+    fn search_archetype(&self, archetype_descriptor: &ArchetypeDescriptor) -> Result<u16, usize> {
+        let mappings = &self.sorted_mappings[archetype_descriptor.len() as usize - 1];
+        let archetype_id = archetype_descriptor.archetype_id();
+        let mut position = mappings.partition_point(|e| e.id < archetype_id);
+        while let Some(key) = mappings.get(position).filter(|e| e.id == archetype_id) {
+            if self.archetypes[key.archetype_index as usize]
+                .descriptor()
+                .has_same_components(archetype_descriptor)
+            {
+                return Ok(key.archetype_index);
+            }
+            position += 1;
+        }
+        Err(position)
+    }
+
+    /// Caches the transition between two archetypes in both directions.
+    /// The `larger` archetype must contain exactly the components of `smaller` plus the given component type.
+    /// Returns an error if the edges could not be allocated, in which case neither edge is cached.
+    fn link_archetypes(
+        &mut self,
+        smaller: u16,
+        larger: u16,
+        component_type_id: ComponentTypeId,
+    ) -> Result<(), RegistryErrorKind> {
+        // Grow both edge lists up front, so the transition is never cached in only one direction.
+        self.archetypes[smaller as usize]
+            .try_reserve_edge()
+            .map_err(|_| RegistryErrorKind::AllocationFailed)?;
+        self.archetypes[larger as usize]
+            .try_reserve_edge()
+            .map_err(|_| RegistryErrorKind::AllocationFailed)?;
+        self.archetypes[smaller as usize].set_add_edge(component_type_id, larger);
+        self.archetypes[larger as usize].set_remove_edge(component_type_id, smaller);
+        Ok(())
+    }
+
     pub fn find_or_create_archetype(
         &mut self,
         archetype_descriptor: &ArchetypeDescriptor,
-    ) -> Option<(u16, &mut Archetype)> {
+    ) -> Option<(u16, &mut Archetype<A>)> {
+        match self.try_find_or_create_archetype(archetype_descriptor) {
+            Ok(v) => Some(v),
+            Err(RegistryErrorKind::AllocationFailed) => panic!("Archetype allocation failed."),
+            Err(_) => None,
+        }
+    }
+
+    /// Finds or creates the archetype matching the given archetype descriptor.
+    /// Returns the reason of failure instead of panicking if the new archetype could not be allocated.
+    pub fn try_find_or_create_archetype(
+        &mut self,
+        archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Result<(u16, &mut Archetype<A>), RegistryErrorKind> {
         let len = archetype_descriptor.len() as usize;
-        if len > MAX_COMPONENTS_PER_ENTITY || !archetype_descriptor.is_valid() {
-            return None;
+        if len > MAX_COMPONENTS_PER_ENTITY {
+            return Err(RegistryErrorKind::LimitExceeded);
         }
-        return match self.sorted_mappings[len - 1]
-            .binary_search_by_key(&archetype_descriptor.archetype_id(), |e| e.id)
-        {
-            Ok(found_index) => Some((
-                self.sorted_mappings[len - 1][found_index].archetype_index,
-                &mut self.archetypes
-                    [self.sorted_mappings[len - 1][found_index].archetype_index as usize],
+        if !archetype_descriptor.is_valid() {
+            return Err(RegistryErrorKind::InvalidComponentGroup);
+        }
+        if len == 0 {
+            return Ok((
+                Self::EMPTY_ARCHETYPE_INDEX,
+                &mut self.archetypes[Self::EMPTY_ARCHETYPE_INDEX as usize],
+            ));
+        }
+        return match self.search_archetype(archetype_descriptor) {
+            Ok(archetype_index) => Ok((
+                archetype_index,
+                &mut self.archetypes[archetype_index as usize],
             )),
             Err(insertion_index) => {
                 if self.archetypes.len() >= MAX_ARCHETYPE_COUNT {
-                    return None;
+                    return Err(RegistryErrorKind::LimitExceeded);
                 }
-
-                let archetype = Archetype::with_capacity(
+                // Grow the storage up front, so pushing the archetype and its key can not fail.
+                self.archetypes
+                    .try_reserve(1)
+                    .map_err(|_| RegistryErrorKind::AllocationFailed)?;
+                self.sorted_mappings[len - 1]
+                    .try_reserve(1)
+                    .map_err(|_| RegistryErrorKind::AllocationFailed)?;
+
+                let archetype = Archetype::try_with_capacity_in(
                     archetype_descriptor,
                     DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32,
-                );
+                    self.allocator.clone(),
+                )
+                .map_err(|_| RegistryErrorKind::AllocationFailed)?;
                 let key = SortedArchetypeKey {
                     id: archetype_descriptor.archetype_id(),
                     archetype_index: self.archetypes.len() as u16,
                 };
                 self.archetypes.push(archetype);
                 self.sorted_mappings[len - 1].insert(insertion_index, key);
-                Some((
+                Ok((
                     self.archetypes.len() as u16 - 1,
                     self.archetypes.last_mut().unwrap(),
                 ))
@@ -203,39 +458,90 @@ impl ArchetypeRegistry {
     /// Returns an archetype reference for the given index.
     /// # Safety
     /// Index must not be out of bounds.
-    pub unsafe fn get_unchecked(&self, index: u16) -> &Archetype {
+    pub unsafe fn get_unchecked(&self, index: u16) -> &Archetype<A> {
         self.archetypes.get_unchecked(index as usize)
     }
 
     /// Returns a mutable archetype reference for the given index.
     /// # Safety
     /// Index must not be out of bounds.
-    pub unsafe fn get_unchecked_mut(&mut self, index: u16) -> &mut Archetype {
+    pub unsafe fn get_unchecked_mut(&mut self, index: u16) -> &mut Archetype<A> {
         self.archetypes.get_unchecked_mut(index as usize)
     }
 
     pub fn iter_components_matching<'a, G: ComponentGroup>(
         &'a self,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceRefTuple<'a>> {
-        MatchingIter::<'a, G>::new(&self.sorted_mappings, &self.archetypes)
+        MatchingIter::<'a, A, G>::new(&self.sorted_mappings, &self.archetypes)
     }
 
     pub fn iter_components_matching_mut<'a, G: ComponentGroup>(
         &'a mut self,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'a>> {
-        MatchingIterMut::<'a, G>::new(&self.sorted_mappings, &mut self.archetypes)
+        MatchingIterMut::<'a, A, G>::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            self.change_tick.load(Ordering::Relaxed),
+        )
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn par_iter_components_matching_mut<'a, G: ComponentGroup>(
+        &'a mut self,
+    ) -> impl rayon::iter::ParallelIterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'a>>
+    {
+        use rayon::iter::IntoParallelIterator;
+        // Large archetypes are split into multiple chunks to balance the work between threads.
+        let mut chunks = Vec::new();
+        for (entities, mut slices) in EntityMatchingIterMut::<'a, A, G>::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            self.change_tick.load(Ordering::Relaxed),
+        ) {
+            let mut len = entities.len();
+            while len > PARALLEL_CHUNK_SIZE {
+                let (chunk, remainder) = G::split_slices_at_mut(slices, PARALLEL_CHUNK_SIZE);
+                chunks.push(chunk);
+                slices = remainder;
+                len -= PARALLEL_CHUNK_SIZE;
+            }
+            if len > 0 {
+                chunks.push(slices);
+            }
+        }
+        chunks.into_par_iter()
     }
 
     pub fn iter_entity_components_matching<'a, G: ComponentGroup>(
         &'a self,
     ) -> impl Iterator<Item = (&'a [Entity], <G as ComponentGroup>::SliceRefTuple<'a>)> {
-        EntityMatchingIter::<'a, G>::new(&self.sorted_mappings, &self.archetypes)
+        EntityMatchingIter::<'a, A, G>::new(&self.sorted_mappings, &self.archetypes)
     }
 
     pub fn iter_entity_components_matching_mut<'a, G: ComponentGroup>(
         &'a mut self,
     ) -> impl Iterator<Item = (&'a [Entity], <G as ComponentGroup>::SliceMutRefTuple<'a>)> {
-        EntityMatchingIterMut::<'a, G>::new(&self.sorted_mappings, &mut self.archetypes)
+        EntityMatchingIterMut::<'a, A, G>::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            self.change_tick.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn iter_components<'a, G: ComponentGroup>(
+        &'a self,
+    ) -> impl ExactSizeIterator<Item = (Entity, <G as ComponentGroup>::RefTuple<'a>)> {
+        ComponentIter::<'a, A, G>::new(&self.sorted_mappings, &self.archetypes)
+    }
+
+    pub fn iter_components_mut<'a, G: ComponentGroup>(
+        &'a mut self,
+    ) -> impl ExactSizeIterator<Item = (Entity, <G as ComponentGroup>::MutRefTuple<'a>)> {
+        ComponentIterMut::<'a, A, G>::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            self.change_tick.load(Ordering::Relaxed),
+        )
     }
 
     pub fn iter_filtered_components_matching<
@@ -246,7 +552,11 @@ impl ArchetypeRegistry {
         &'a self,
         filter_closure: F,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceRefTuple<'a>> {
-        FilterMatchingIter::<'a, G, F>::new(&self.sorted_mappings, &self.archetypes, filter_closure)
+        FilterMatchingIter::<'a, A, G, F>::new(
+            &self.sorted_mappings,
+            &self.archetypes,
+            filter_closure,
+        )
     }
 
     pub fn iter_filtered_components_matching_mut<
@@ -257,10 +567,11 @@ impl ArchetypeRegistry {
         &'a mut self,
         filter_closure: F,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'a>> {
-        FilterMatchingIterMut::<'a, G, F>::new(
+        FilterMatchingIterMut::<'a, A, G, F>::new(
             &self.sorted_mappings,
             &mut self.archetypes,
             filter_closure,
+            self.change_tick.load(Ordering::Relaxed),
         )
     }
 
@@ -272,7 +583,7 @@ impl ArchetypeRegistry {
         &'a self,
         filter_closure: F,
     ) -> impl Iterator<Item = (&'a [Entity], <G as ComponentGroup>::SliceRefTuple<'a>)> {
-        FilterEntityMatchingIter::<'a, G, F>::new(
+        FilterEntityMatchingIter::<'a, A, G, F>::new(
             &self.sorted_mappings,
             &self.archetypes,
             filter_closure,
@@ -287,23 +598,113 @@ impl ArchetypeRegistry {
         &'a mut self,
         filter_closure: F,
     ) -> impl Iterator<Item = (&'a [Entity], <G as ComponentGroup>::SliceMutRefTuple<'a>)> {
-        FilterEntityMatchingIterMut::<'a, G, F>::new(
+        FilterEntityMatchingIterMut::<'a, A, G, F>::new(
             &self.sorted_mappings,
             &mut self.archetypes,
             filter_closure,
+            self.change_tick.load(Ordering::Relaxed),
+        )
+    }
+
+    pub(crate) fn iter_archetypes(&self) -> impl Iterator<Item = &Archetype<A>> {
+        self.archetypes.iter()
+    }
+
+    /// Shrinks the capacity of all archetypes and the internal lookup tables as much as possible.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        for archetype in self.archetypes.iter_mut() {
+            archetype.shrink_to_fit();
+        }
+        self.archetypes.shrink_to_fit();
+        for mapping in self.sorted_mappings.iter_mut() {
+            mapping.shrink_to_fit();
+        }
+    }
+
+    pub(crate) fn iter_archetypes_mut(&mut self) -> impl Iterator<Item = &mut Archetype<A>> {
+        self.archetypes.iter_mut()
+    }
+
+    pub(crate) fn iter_filtered_archetypes_matching_mut<
+        G: ComponentGroup,
+        F: Fn(&ArchetypeDescriptor) -> bool,
+    >(
+        &mut self,
+        filter_closure: F,
+    ) -> impl Iterator<Item = &mut Archetype<A>> {
+        FilterArchetypeIterMut::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            G::DESCRIPTOR.archetype(),
+            filter_closure,
+        )
+    }
+
+    pub fn iter_query<'a, F: ReadOnlyQueryFetch, R: QueryFilter>(
+        &'a self,
+        last_run: u32,
+        this_run: u32,
+    ) -> impl Iterator<Item = (Entity, F::Item<'a>)> {
+        let archetypes = FilterArchetypeIter::new(
+            &self.sorted_mappings,
+            &self.archetypes,
+            &F::DESCRIPTOR,
+            R::matches as fn(&ArchetypeDescriptor) -> bool,
+        );
+        unsafe { QueryIter::<'a, A, F, R, _>::new(archetypes, last_run, this_run) }
+    }
+
+    pub fn iter_query_mut<'a, F: QueryFetch, R: QueryFilter>(
+        &'a mut self,
+        last_run: u32,
+        this_run: u32,
+    ) -> impl Iterator<Item = (Entity, F::Item<'a>)> {
+        let archetypes = FilterArchetypeIterMut::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            &F::DESCRIPTOR,
+            R::matches as fn(&ArchetypeDescriptor) -> bool,
+        )
+        .map(|archetype| &*archetype);
+        unsafe { QueryIter::<'a, A, F, R, _>::new(archetypes, last_run, this_run) }
+    }
+
+    pub fn iter_query_chunks<'a, F: ReadOnlyQueryFetch, R: ArchetypeFilter>(
+        &'a self,
+    ) -> impl Iterator<Item = (&'a [Entity], F::Slice<'a>)> {
+        let archetypes = FilterArchetypeIter::new(
+            &self.sorted_mappings,
+            &self.archetypes,
+            &F::DESCRIPTOR,
+            R::matches as fn(&ArchetypeDescriptor) -> bool,
+        );
+        unsafe { QueryChunkIter::<'a, A, F, _>::new(archetypes, self.change_tick()) }
+    }
+
+    pub fn iter_query_chunks_mut<'a, F: QueryFetch, R: ArchetypeFilter>(
+        &'a mut self,
+        change_tick: u32,
+    ) -> impl Iterator<Item = (&'a [Entity], F::Slice<'a>)> {
+        let archetypes = FilterArchetypeIterMut::new(
+            &self.sorted_mappings,
+            &mut self.archetypes,
+            &F::DESCRIPTOR,
+            R::matches as fn(&ArchetypeDescriptor) -> bool,
         )
+        .map(|archetype| &*archetype);
+        unsafe { QueryChunkIter::<'a, A, F, _>::new(archetypes, change_tick) }
     }
 }
 
-impl Index<u16> for ArchetypeRegistry {
-    type Output = Archetype;
+impl<A: ArchetypeAllocator> Index<u16> for ArchetypeRegistry<A> {
+    type Output = Archetype<A>;
 
     fn index(&self, index: u16) -> &Self::Output {
         &self.archetypes[index as usize]
     }
 }
 
-impl IndexMut<u16> for ArchetypeRegistry {
+impl<A: ArchetypeAllocator> IndexMut<u16> for ArchetypeRegistry<A> {
     fn index_mut(&mut self, index: u16) -> &mut Self::Output {
         &mut self.archetypes[index as usize]
     }
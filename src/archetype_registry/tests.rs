@@ -2,6 +2,7 @@ use crate::archetype_registry::ArchetypeRegistry;
 use crate::descriptors::component_group::ComponentGroup;
 use crate::entity_registry::*;
 use crate::test_components::*;
+use crate::Component;
 
 #[test]
 fn test_archetype_registry() {
@@ -43,3 +44,156 @@ fn test_archetype_registry() {
         }
     }
 }
+
+#[test]
+fn test_archetype_registry_edges() {
+    let mut registry = ArchetypeRegistry::default();
+    let (ab_index, _) = registry
+        .find_or_create_archetype(<(A, B) as ComponentGroup>::DESCRIPTOR.archetype())
+        .unwrap();
+    assert_eq!(registry[ab_index].add_edge(C::ID), None);
+    assert_eq!(registry[ab_index].remove_edge(B::ID), None);
+
+    // Adding a component creates the edge in both directions.
+    let (_, abc_index, destination) = registry
+        .find_or_create_archetype_adding_component(ab_index, &<C as Component>::DESCRIPTOR)
+        .unwrap();
+    assert!(destination.descriptor().has_component::<C>());
+    assert_eq!(registry[ab_index].add_edge(C::ID), Some(abc_index));
+    assert_eq!(registry[abc_index].remove_edge(C::ID), Some(ab_index));
+
+    // Following the edge yields the same archetype.
+    let (_, index, _) = registry
+        .find_or_create_archetype_adding_component(ab_index, &<C as Component>::DESCRIPTOR)
+        .unwrap();
+    assert_eq!(index, abc_index);
+    let (_, index, _) = registry
+        .find_or_create_archetype_removing_component(abc_index, &<C as Component>::DESCRIPTOR)
+        .unwrap();
+    assert_eq!(index, ab_index);
+
+    // Removing a component creates the edge in both directions.
+    let (_, bc_index, destination) = registry
+        .find_or_create_archetype_removing_component(abc_index, &<A as Component>::DESCRIPTOR)
+        .unwrap();
+    assert_eq!(
+        destination.descriptor().archetype_id(),
+        <(B, C) as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .archetype_id()
+    );
+    assert_eq!(registry[abc_index].remove_edge(A::ID), Some(bc_index));
+    assert_eq!(registry[bc_index].add_edge(A::ID), Some(abc_index));
+
+    // Creating new archetypes must not invalidate existing edges.
+    let (a_index, _) = registry
+        .find_or_create_archetype(<A as ComponentGroup>::DESCRIPTOR.archetype())
+        .unwrap();
+    registry
+        .find_or_create_archetype(<(A, C) as ComponentGroup>::DESCRIPTOR.archetype())
+        .unwrap();
+    let (_, index, _) = registry
+        .find_or_create_archetype_adding_component(a_index, &<B as Component>::DESCRIPTOR)
+        .unwrap();
+    assert_eq!(index, ab_index);
+    assert_eq!(registry[a_index].add_edge(B::ID), Some(ab_index));
+    assert_eq!(registry[ab_index].remove_edge(B::ID), Some(a_index));
+    for (index, component_type_id) in [(ab_index, C::ID), (bc_index, A::ID)] {
+        let destination = registry[index].add_edge(component_type_id).unwrap();
+        assert_eq!(
+            registry[destination].descriptor().archetype_id(),
+            <(A, B, C) as ComponentGroup>::DESCRIPTOR
+                .archetype()
+                .archetype_id()
+        );
+    }
+
+    // Transitions that are impossible are not cached.
+    assert!(registry
+        .find_or_create_archetype_adding_component(ab_index, &<A as Component>::DESCRIPTOR)
+        .is_none());
+    assert_eq!(registry[ab_index].add_edge(A::ID), None);
+}
+
+#[test]
+fn test_archetype_registry_add_remove_multiple() {
+    let mut registry = ArchetypeRegistry::default();
+    let (a_index, _) = registry
+        .find_or_create_archetype(<A as ComponentGroup>::DESCRIPTOR.archetype())
+        .unwrap();
+    let archetype_count = registry.archetypes.len();
+
+    // Only the destination archetype is created, no intermediate ones.
+    let (_, abc_index, destination) = registry
+        .find_or_create_archetype_adding_components(
+            a_index,
+            <(C, B) as ComponentGroup>::DESCRIPTOR.archetype(),
+        )
+        .unwrap();
+    assert_eq!(
+        destination.descriptor().archetype_id(),
+        <(A, B, C) as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .archetype_id()
+    );
+    assert_eq!(registry.archetypes.len(), archetype_count + 1);
+
+    let (_, index, _) = registry
+        .find_or_create_archetype_removing_components(
+            abc_index,
+            <(B, C) as ComponentGroup>::DESCRIPTOR.archetype(),
+        )
+        .unwrap();
+    assert_eq!(index, a_index);
+    assert_eq!(registry.archetypes.len(), archetype_count + 1);
+
+    assert!(registry
+        .find_or_create_archetype_adding_components(
+            abc_index,
+            <(A, DropLogA) as ComponentGroup>::DESCRIPTOR.archetype(),
+        )
+        .is_none());
+    assert!(registry
+        .find_or_create_archetype_removing_components(
+            a_index,
+            <(A, B) as ComponentGroup>::DESCRIPTOR.archetype(),
+        )
+        .is_none());
+}
+
+#[test]
+fn test_archetype_registry_archetype_id_collision() {
+    use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+    use crate::descriptors::component_descriptor::ComponentDescriptor;
+
+    let mut registry = ArchetypeRegistry::default();
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut components = [ComponentDescriptor::INVALID; crate::MAX_COMPONENTS_PER_ENTITY];
+    components[0] = <A as Component>::DESCRIPTOR;
+    components[1] = <C as Component>::DESCRIPTOR;
+    // Forge a descriptor for (A, C) which collides with the id of (A, B).
+    let colliding = ArchetypeDescriptor::new(descriptor.archetype_id(), 2, components);
+    assert!(!colliding.has_same_components(descriptor));
+
+    let (index, _) = registry.find_or_create_archetype(descriptor).unwrap();
+    let (colliding_index, _) = registry.find_or_create_archetype(&colliding).unwrap();
+    assert_ne!(index, colliding_index);
+    assert_eq!(
+        registry.find_or_create_archetype(descriptor).unwrap().0,
+        index
+    );
+    assert_eq!(
+        registry.find_or_create_archetype(&colliding).unwrap().0,
+        colliding_index
+    );
+    assert!(registry
+        .find_archetype(&colliding)
+        .unwrap()
+        .descriptor()
+        .has_component::<C>());
+    assert!(registry
+        .find_archetype(descriptor)
+        .unwrap()
+        .descriptor()
+        .has_component::<B>());
+}
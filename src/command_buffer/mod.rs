@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests;
+
+use crate::allocator::{ArchetypeAllocator, Global};
+use crate::descriptors::component_group::ComponentGroup;
+use crate::entity_registry::EntityReserver;
+use crate::{Component, Entity, Registry};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+type Command<A> = Box<dyn FnOnce(&mut Registry<A>) + Send>;
+
+/// Records structural changes to a registry so they can be applied later using [`Registry::apply`].
+/// This allows entities to be created and destroyed and components to be added, removed or replaced
+/// while the registry is borrowed, e.g. during iteration.
+/// Commands are applied in the order they were recorded. Commands failing to apply are skipped,
+/// any components they contain are dropped.
+/// Entities reserved by a command buffer which is dropped without being applied stay reserved,
+/// see [`CommandBuffer::create_entity`].
+pub struct CommandBuffer<A: ArchetypeAllocator = Global> {
+    reserver: EntityReserver,
+    commands: Vec<Command<A>>,
+}
+
+impl<A: ArchetypeAllocator> CommandBuffer<A> {
+    /// Creates a new command buffer for the given registry.
+    /// The command buffer may only be applied to this registry.
+    pub fn new(registry: &Registry<A>) -> Self {
+        Self {
+            reserver: registry.entity_reserver().clone(),
+            commands: Vec::new(),
+        }
+    }
+
+    /// Returns the amount of recorded commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if no commands are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Records the creation of an entity using the provided components.
+    /// Returns the reserved entity handle, which may be used in later commands right away.
+    /// The entity becomes available in the registry once the command buffer is applied.
+    /// If the entity was created earlier by [`Registry::flush_reserved`], the components are added to it instead.
+    /// Returns Err(components) if the maximum amount of entities is exceeded.
+    /// If the command buffer is dropped without being applied, the entity remains reserved.
+    /// It is then only created by [`Registry::flush_reserved`] or [`Registry::flush_reserved_empty`],
+    /// without flushing its slot is never reused.
+    pub fn create_entity<G: ComponentGroup + Send>(&mut self, components: G) -> Result<Entity, G> {
+        let entity = match self.reserver.reserve_entity() {
+            Some(v) => v,
+            None => return Err(components),
+        };
+        self.commands
+            .push(Box::new(move |registry: &mut Registry<A>| {
+                // The entity may have been created already by flushing the reserved entities.
+                if let Err(components) = registry.create_reserved_entity(entity, components) {
+                    let _ = registry.add_components(entity, components);
+                }
+            }));
+        Ok(entity)
+    }
+
+    /// Records the destruction of the entity. See [`Registry::destroy_entity`].
+    pub fn destroy_entity(&mut self, entity: Entity) {
+        self.commands
+            .push(Box::new(move |registry: &mut Registry<A>| {
+                registry.destroy_entity(entity);
+            }));
+    }
+
+    /// Records adding the component to the entity. See [`Registry::add_component`].
+    pub fn add_component<C: Component>(&mut self, entity: Entity, component: C) {
+        self.commands
+            .push(Box::new(move |registry: &mut Registry<A>| {
+                let _ = registry.add_component(entity, component);
+            }));
+    }
+
+    /// Records removing the component from the entity, the removed component is dropped.
+    /// See [`Registry::remove_component`].
+    pub fn remove_component<C: Component>(&mut self, entity: Entity) {
+        self.commands
+            .push(Box::new(move |registry: &mut Registry<A>| {
+                let _ = registry.remove_component::<C>(entity);
+            }));
+    }
+
+    /// Records replacing the component of the entity, the replaced component is dropped.
+    /// See [`Registry::replace_component`].
+    pub fn replace_component<C: Component>(&mut self, entity: Entity, component: C) {
+        self.commands
+            .push(Box::new(move |registry: &mut Registry<A>| {
+                let _ = registry.replace_component(entity, component);
+            }));
+    }
+
+    pub(crate) fn reserver(&self) -> &EntityReserver {
+        &self.reserver
+    }
+
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = Command<A>> + '_ {
+        self.commands.drain(..)
+    }
+}
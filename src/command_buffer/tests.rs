@@ -0,0 +1,89 @@
+use crate::test_components::*;
+use crate::*;
+use alloc::vec::Vec;
+
+#[test]
+fn test_command_buffer_during_iteration() {
+    let mut registry = Registry::default();
+    for i in 0..4 {
+        registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+    }
+    let mut commands = CommandBuffer::new(&registry);
+
+    let mut created = Vec::new();
+    for (entity, (a, _)) in registry.iter_mut::<(A, B)>() {
+        match a._data {
+            0 => commands.destroy_entity(entity),
+            1 => commands.remove_component::<B>(entity),
+            2 => commands.add_component(entity, C { _data: 2 }),
+            _ => commands.replace_component(entity, B { _data: 3 }),
+        }
+        let child = commands
+            .create_entity(A {
+                _data: a._data + 10,
+            })
+            .unwrap();
+        commands.add_component(child, DropLogA::default());
+        created.push(child);
+    }
+    assert_eq!(commands.len(), 12);
+    for entity in &created {
+        assert!(entity.is_valid());
+        assert!(!registry.has_component::<A>(*entity));
+    }
+    // Entities created directly in between do not collide with the reserved ones.
+    let direct = registry.create_entity(A::default()).unwrap();
+    assert!(!created.contains(&direct));
+
+    registry.apply(&mut commands);
+    assert!(commands.is_empty());
+    for (i, entity) in created.iter().enumerate() {
+        assert_eq!(registry.get_component::<A>(*entity).unwrap()._data, i + 10);
+        assert!(registry.has_component::<DropLogA>(*entity));
+    }
+    assert_eq!(registry.iter_entities().count(), 8);
+    assert_eq!(registry.iter::<C>().count(), 1);
+    assert_eq!(registry.iter::<(A, B)>().count(), 2);
+    let (_, (_, b)) = registry
+        .iter::<(A, B)>()
+        .find(|(_, (a, _))| a._data == 3)
+        .unwrap();
+    assert_eq!(b._data, 3);
+}
+
+#[test]
+fn test_command_buffer_failed_create() {
+    let mut registry = Registry::default();
+    let mut commands = CommandBuffer::new(&registry);
+    let entity = commands.create_entity(A::default()).unwrap();
+    commands.destroy_entity(entity);
+    let dropped = commands.create_entity(B::default()).unwrap();
+    drop(commands);
+    registry.apply(&mut CommandBuffer::new(&registry));
+    assert!(!registry.has_component::<B>(dropped));
+
+    // The reservation of the dropped command buffer is kept until it is flushed.
+    assert!(!registry.contains(dropped));
+    registry.flush_reserved_empty();
+    assert!(registry.contains(dropped));
+    assert!(registry.destroy_entity(entity));
+    assert!(registry.destroy_entity(dropped));
+
+    let mut commands = CommandBuffer::new(&registry);
+    let entity = commands.create_entity(A::default()).unwrap();
+    commands.destroy_entity(entity);
+    registry.apply(&mut commands);
+    assert!(!registry.has_component::<A>(entity));
+    assert_eq!(registry.iter_entities().count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_command_buffer_different_registry() {
+    let registry = Registry::default();
+    let mut commands = CommandBuffer::new(&registry);
+    let mut other = Registry::default();
+    other.apply(&mut commands);
+}
@@ -1,21 +1,84 @@
 /// Used amount of bits in the entity handle for storing the index.
 /// Is defined to be 24 bits. Thus, yielding 2^24 - 1 = 16_777_215 different indices/entities.
+#[cfg(not(feature = "wide_entity_handles"))]
 pub const ENTITY_HANDLE_BITS: u8 = 24;
 
+/// Used amount of bits in the entity handle for storing the index.
+/// Is defined to be 32 bits. Thus, yielding 2^32 - 1 = 4_294_967_295 different indices/entities.
+#[cfg(feature = "wide_entity_handles")]
+pub const ENTITY_HANDLE_BITS: u8 = 32;
+
 /// The maximum value that an entity can be.
+#[cfg(not(feature = "wide_entity_handles"))]
 pub const MAX_ENTITY_HANDLE_VALUE: u32 = 16_777_215;
 
+/// The maximum value that an entity can be.
+#[cfg(feature = "wide_entity_handles")]
+pub const MAX_ENTITY_HANDLE_VALUE: u32 = u32::MAX;
+
 /// Value indicating that an entity handle is invalid.
 /// The null entity is the entity having the handle value being this value, and version is max version value.
-/// This corresponds to 2^24.
+/// This corresponds to 2^24, one past the largest index of the 24 bit index and 8 bit version handle layout.
+#[cfg(not(feature = "wide_entity_handles"))]
 pub const INVALID_ENTITY_HANDLE_VALUE: u32 = 16_777_216;
 
+/// Value indicating that an entity handle is invalid.
+/// The null entity is the entity having the handle value being this value, and version is max version value.
+/// This corresponds to 2^32, one past the largest index of the 32 bit index and 32 bit version handle layout.
+#[cfg(feature = "wide_entity_handles")]
+pub const INVALID_ENTITY_HANDLE_VALUE: u64 = 4_294_967_296;
+
 /// Used amount of bits in an entity for storing the version.
 /// Is defined to be 8 bits. Thus, yielding 2^8 = 256 different versions.
+#[cfg(not(feature = "wide_entity_handles"))]
 pub const ENTITY_VERSION_BITS: u8 = 8;
 
+/// Used amount of bits in an entity for storing the version.
+/// Is defined to be 32 bits. Thus, yielding 2^32 = 4_294_967_296 different versions.
+#[cfg(feature = "wide_entity_handles")]
+pub const ENTITY_VERSION_BITS: u8 = 32;
+
 /// The maximum value that an entity version can be.
-pub const MAX_ENTITY_VERSION_VALUE: u8 = 255;
+pub const MAX_ENTITY_VERSION_VALUE: EntityVersion = EntityVersion::MAX;
+
+/// The type storing the version of an entity.
+#[cfg(not(feature = "wide_entity_handles"))]
+pub type EntityVersion = u8;
+
+/// The type storing the version of an entity.
+#[cfg(feature = "wide_entity_handles")]
+pub type EntityVersion = u32;
+
+/// The type storing the raw handle of an entity, consisting of its index and version.
+#[cfg(not(feature = "wide_entity_handles"))]
+pub type RawEntityHandle = u32;
+
+/// The type storing the raw handle of an entity, consisting of its index and version.
+#[cfg(feature = "wide_entity_handles")]
+pub type RawEntityHandle = u64;
+
+/// The type storing the raw value of a component type id.
+#[cfg(not(feature = "wide_component_ids"))]
+pub type RawComponentTypeId = u16;
+
+/// The type storing the raw value of a component type id.
+#[cfg(feature = "wide_component_ids")]
+pub type RawComponentTypeId = u64;
+
+/// The type storing the raw value of an archetype id.
+#[cfg(not(any(feature = "wide_archetype_ids", feature = "extra_wide_archetype_ids")))]
+pub type RawArchetypeId = u32;
+
+/// The type storing the raw value of an archetype id.
+#[cfg(all(
+    feature = "wide_archetype_ids",
+    not(feature = "extra_wide_archetype_ids")
+))]
+pub type RawArchetypeId = u64;
+
+/// The type storing the raw value of an archetype id.
+#[cfg(feature = "extra_wide_archetype_ids")]
+pub type RawArchetypeId = u128;
 
 /// The maximum number of components an entity is allowed to have.
 pub const MAX_COMPONENTS_PER_ENTITY: usize = 14;
@@ -26,8 +89,22 @@ pub const MAX_ARCHETYPE_COUNT: usize = (u16::MAX - 1) as usize;
 /// Valid archetype indices can never have this value.
 pub const INVALID_ARCHETYPE_INDEX: u16 = u16::MAX;
 
+/// Archetype index used by entries of reserved entities which have not been created yet.
+/// Valid archetype indices can never have this value.
+pub const RESERVED_ARCHETYPE_INDEX: u16 = u16::MAX - 1;
+
 /// Maximum amount of entities that can be stored for a given archetype.
+#[cfg(not(feature = "wide_entity_handles"))]
 pub const MAX_ENTITIES_PER_ARCHETYPE: u32 = MAX_ENTITY_HANDLE_VALUE;
 
+/// Maximum amount of entities that can be stored for a given archetype.
+#[cfg(feature = "wide_entity_handles")]
+pub const MAX_ENTITIES_PER_ARCHETYPE: u32 = MAX_ENTITY_HANDLE_VALUE - 1;
+
 /// The default amount of elements for which space is reserved if an entity is pushed into an empty archetype.
 pub const DEFAULT_ARCHETYPE_ALLOCATION_SIZE: usize = 128;
+
+/// The maximum amount of entities in a single chunk handed out by the parallel iterators.
+/// Larger archetypes are split into multiple chunks.
+#[cfg(feature = "parallel")]
+pub const PARALLEL_CHUNK_SIZE: usize = 1024;
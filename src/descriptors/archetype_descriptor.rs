@@ -1,10 +1,10 @@
 use crate::descriptors::component_descriptor::ComponentDescriptor;
-use crate::fnv1a::fnv1a_hash_32;
 use crate::{constants::*, ArchetypeId, Component, ComponentTypeId};
 
 /// Represents a combination of components.
 /// Each component type MUST be unique (i.e. no duplicate component types).
-/// Length must be larger than 0 and lower or equal to [`MAX_COMPONENTS_PER_ENTITY`].
+/// Length must be lower or equal to [`MAX_COMPONENTS_PER_ENTITY`].
+/// Length must be larger than 0, except for [`ArchetypeDescriptor::EMPTY`].
 /// Use the [`ArchetypeDescriptor::is_valid`] function to check for validity.
 /// Any use of an invalid archetype descriptor is considered UB.
 #[derive(Debug, Clone)]
@@ -22,9 +22,16 @@ impl ArchetypeDescriptor {
         components: [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY],
     };
 
+    /// The descriptor of the empty archetype. Has 0 components and an ArchetypeId of [`ArchetypeId::EMPTY`].
+    pub const EMPTY: ArchetypeDescriptor = ArchetypeDescriptor {
+        archetype_id: ArchetypeId::EMPTY,
+        len: 0,
+        components: [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY],
+    };
+
     /// Returns true if it is a valid archetype.
     /// A valid archetype has a length larger than 0 and smaller than [`MAX_COMPONENTS_PER_ENTITY`].
-    /// It also contains no duplicate components.
+    /// It also contains no duplicate components. The empty archetype is valid as well.
     pub const fn is_valid(&self) -> bool {
         self.archetype_id.is_valid()
     }
@@ -46,12 +53,16 @@ impl ArchetypeDescriptor {
     }
 
     /// Computes an archetype ID, returns [`ArchetypeId::INVALID`] if given an invalid combination of components.
+    /// Archetype ids are hashes, use [`ArchetypeDescriptor::has_same_components`] to rule out collisions.
+    #[allow(clippy::unnecessary_cast)] // The raw ids have the same width with some features.
     pub const fn compute_archetype_id(descriptors: &[ComponentDescriptor]) -> ArchetypeId {
         if descriptors.is_empty() {
             return ArchetypeId::INVALID;
         }
         if descriptors.len() == 1 {
-            return ArchetypeId::from_u32(descriptors[0].component_type_id().into_u16() as u32);
+            return ArchetypeId::from_raw(
+                descriptors[0].component_type_id().into_raw() as RawArchetypeId
+            );
         }
 
         let mut bytes = [0; MAX_COMPONENTS_PER_ENTITY * core::mem::size_of::<ComponentTypeId>()];
@@ -65,53 +76,52 @@ impl ArchetypeDescriptor {
             }
             i += 1;
         }
-        ArchetypeId::from_u32(fnv1a_hash_32(&bytes, Some(bytes.len())))
+        ArchetypeId::from_hash(&bytes)
+    }
+
+    /// Returns whether both descriptors contain exactly the same component types.
+    /// Unlike comparing archetype ids, this is not affected by hash collisions.
+    pub fn has_same_components(&self, descriptor: &ArchetypeDescriptor) -> bool {
+        self.len() == descriptor.len()
+            && self
+                .components()
+                .iter()
+                .zip(descriptor.components())
+                .all(|(a, b)| a.is_same_component_type(b))
     }
 
     /// Returns whether the descriptor provided is contained in self. (i.e. subset inclusion)
     /// Do not provide an invalid descriptor to this!
-    pub const fn contains_subset(&self, descriptor: &ArchetypeDescriptor) -> bool {
-        if descriptor.len() > self.len() {
-            return false;
-        }
-        let mut i = 0;
-        'outer_loop: while i < descriptor.len() {
-            let mut j = 0;
-            while j < self.len() {
-                if self.components[j as usize].component_type_id.into_u16()
-                    == descriptor.components[i as usize]
-                        .component_type_id
-                        .into_u16()
-                {
-                    i += 1;
-                    continue 'outer_loop;
-                }
-                j += 1;
-            }
-            return false;
-        }
-        true
+    pub fn contains_subset(&self, descriptor: &ArchetypeDescriptor) -> bool {
+        descriptor.len() <= self.len()
+            && descriptor
+                .components()
+                .iter()
+                .all(|component| self.contains_component(component))
     }
 
     /// Returns whether the descriptor provided is excluded from self. (i.e. subset exclusion)
     /// Do not provide an invalid descriptor to this!
-    pub const fn excludes_subset(&self, descriptor: &ArchetypeDescriptor) -> bool {
-        let mut i = 0;
-        while i < descriptor.len() {
-            let mut j = 0;
-            while j < self.len() {
-                if self.components[j as usize].component_type_id.into_u16()
-                    == descriptor.components[i as usize]
-                        .component_type_id
-                        .into_u16()
-                {
-                    return false;
-                }
-                j += 1;
-            }
-            i += 1;
-        }
-        true
+    pub fn excludes_subset(&self, descriptor: &ArchetypeDescriptor) -> bool {
+        !descriptor
+            .components()
+            .iter()
+            .any(|component| self.contains_component(component))
+    }
+
+    /// Returns whether the archetype descriptor has the component type of the given component descriptor.
+    /// Component types sharing the component type id are not considered the same, see [`ComponentDescriptor::is_same_component_type`].
+    pub fn contains_component(&self, component: &ComponentDescriptor) -> bool {
+        self.component_index(component).is_some()
+    }
+
+    /// Returns the index of the given component type within the components of the archetype descriptor, if present.
+    /// Component types sharing the component type id are not considered the same, see [`ComponentDescriptor::is_same_component_type`].
+    pub fn component_index(&self, component: &ComponentDescriptor) -> Option<usize> {
+        self.components()
+            .binary_search_by_key(&component.component_type_id, |e| e.component_type_id)
+            .ok()
+            .filter(|&index| self.components[index].is_same_component_type(component))
     }
 
     /// Returns a new archetype with the given component type added to it.
@@ -137,7 +147,7 @@ impl ArchetypeDescriptor {
             Ok(_) => None, // Current archetype already contains given component.
             Err(insertion_index) => {
                 let mut v = self.clone();
-                for i in insertion_index..self.len() as usize + 1 {
+                for i in insertion_index..self.len() as usize {
                     v.components[i + 1] = self.components[i].clone();
                 }
                 v.components[insertion_index] = component_descriptor.clone();
@@ -150,18 +160,20 @@ impl ArchetypeDescriptor {
         }
     }
 
+    /// Returns a new archetype with the given component type removed from it.
+    /// Returns the empty archetype if the given component type is the only one.
+    /// Returns none if the current archetype does not contain the component type.
     pub fn remove_component(&self, component: ComponentTypeId) -> Option<ArchetypeDescriptor> {
-        if self.len() as usize == 1 {
-            return None; // Archetype cannot contain zero components.
-        }
         match self.components[0..self.len() as usize]
             .binary_search_by_key(&component, |e| e.component_type_id)
         {
+            Ok(_) if self.len() == 1 => Some(Self::EMPTY),
             Ok(found_index) => {
                 let mut v = self.clone();
-                for i in found_index..self.len() as usize {
+                for i in found_index..self.len() as usize - 1 {
                     v.components[i] = self.components[i + 1].clone();
                 }
+                v.components[self.len() as usize - 1] = ComponentDescriptor::INVALID;
 
                 v.len -= 1;
                 v.archetype_id =
@@ -172,12 +184,78 @@ impl ArchetypeDescriptor {
         }
     }
 
+    /// Returns a new archetype with all component types of the given descriptor added to it.
+    /// Returns none if the current archetype already contains any of the component types,
+    /// or if the resulting archetype would exceed [`MAX_COMPONENTS_PER_ENTITY`].
+    pub fn add_components(&self, descriptor: &ArchetypeDescriptor) -> Option<ArchetypeDescriptor> {
+        let len = self.len() as usize + descriptor.len() as usize;
+        if len > MAX_COMPONENTS_PER_ENTITY {
+            return None;
+        }
+        let mut v = self.clone();
+        // Merge both sorted component lists.
+        let (mut i, mut j) = (0, 0);
+        while i < self.len() as usize || j < descriptor.len() as usize {
+            let take_own = if i == self.len() as usize {
+                false
+            } else if j == descriptor.len() as usize {
+                true
+            } else {
+                match self.components[i]
+                    .component_type_id
+                    .cmp(&descriptor.components[j].component_type_id)
+                {
+                    core::cmp::Ordering::Less => true,
+                    core::cmp::Ordering::Greater => false,
+                    core::cmp::Ordering::Equal => return None, // Already contains component.
+                }
+            };
+            if take_own {
+                v.components[i + j] = self.components[i].clone();
+                i += 1;
+            } else {
+                v.components[i + j] = descriptor.components[j].clone();
+                j += 1;
+            }
+        }
+        v.len = len as u8;
+        v.archetype_id = ArchetypeDescriptor::compute_archetype_id(&v.components[0..len]);
+        Some(v)
+    }
+
+    /// Returns a new archetype with all component types of the given descriptor removed from it.
+    /// Returns the empty archetype if all component types are removed.
+    /// Returns none if the current archetype does not contain all of the component types.
+    pub fn remove_components(
+        &self,
+        descriptor: &ArchetypeDescriptor,
+    ) -> Option<ArchetypeDescriptor> {
+        if !self.contains_subset(descriptor) {
+            return None;
+        }
+        if descriptor.len() == self.len() {
+            return Some(Self::EMPTY);
+        }
+        let mut v = ArchetypeDescriptor::INVALID;
+        let mut len = 0;
+        for component in self.components() {
+            if descriptor
+                .components()
+                .binary_search_by_key(&component.component_type_id, |e| e.component_type_id)
+                .is_err()
+            {
+                v.components[len] = component.clone();
+                len += 1;
+            }
+        }
+        v.len = len as u8;
+        v.archetype_id = ArchetypeDescriptor::compute_archetype_id(&v.components[0..len]);
+        Some(v)
+    }
+
     /// Returns whether the archetype descriptor has a given component type.
     pub fn has_component<C: Component>(&self) -> bool {
-        return self
-            .components()
-            .binary_search_by_key(&C::ID, |e| e.component_type_id)
-            .is_ok();
+        self.contains_component(&C::DESCRIPTOR)
     }
 
     /// Get a the archetype descriptor's archetype id.
@@ -212,7 +290,7 @@ mod tests {
     use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
     use crate::descriptors::component_group::ComponentGroup;
     use crate::test_components::*;
-    use crate::Component;
+    use crate::{ArchetypeId, Component};
 
     #[test]
     fn test_archetype_descriptor_add_remove() {
@@ -230,6 +308,64 @@ mod tests {
         assert!(descriptor.has_component::<A>());
         assert!(descriptor.has_component::<C>());
         assert_eq!(descriptor.len(), 2);
+        assert_eq!(
+            descriptor.archetype_id(),
+            <(A, C) as ComponentGroup>::DESCRIPTOR
+                .archetype()
+                .archetype_id()
+        );
+        let descriptor = descriptor.remove_component(C::ID).unwrap();
+        assert_eq!(
+            descriptor.archetype_id(),
+            <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id()
+        );
+        assert!(descriptor.remove_component(B::ID).is_none());
+        let descriptor = descriptor.remove_component(A::ID).unwrap();
+        assert_eq!(descriptor.archetype_id(), ArchetypeId::EMPTY);
+        assert_eq!(descriptor.len(), 0);
+        let descriptor = descriptor
+            .add_component(&<B as Component>::DESCRIPTOR)
+            .unwrap();
+        assert_eq!(
+            descriptor.archetype_id(),
+            <B as ComponentGroup>::DESCRIPTOR.archetype().archetype_id()
+        );
+    }
+
+    #[test]
+    fn test_archetype_descriptor_add_remove_multiple() {
+        let descriptor: &ArchetypeDescriptor = <B as ComponentGroup>::DESCRIPTOR.archetype();
+        let descriptor = descriptor
+            .add_components(<(C, A) as ComponentGroup>::DESCRIPTOR.archetype())
+            .unwrap();
+        assert_eq!(descriptor.len(), 3);
+        assert_eq!(
+            descriptor.archetype_id(),
+            <(A, B, C) as ComponentGroup>::DESCRIPTOR
+                .archetype()
+                .archetype_id()
+        );
+        assert!(descriptor
+            .add_components(<(A, DropLogA) as ComponentGroup>::DESCRIPTOR.archetype())
+            .is_none());
+
+        let removed = descriptor
+            .remove_components(<(C, A) as ComponentGroup>::DESCRIPTOR.archetype())
+            .unwrap();
+        assert_eq!(
+            removed.archetype_id(),
+            <B as ComponentGroup>::DESCRIPTOR.archetype().archetype_id()
+        );
+        assert!(descriptor
+            .remove_components(<(A, DropLogA) as ComponentGroup>::DESCRIPTOR.archetype())
+            .is_none());
+        assert_eq!(
+            descriptor
+                .remove_components(<(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype())
+                .unwrap()
+                .archetype_id(),
+            ArchetypeId::EMPTY
+        );
     }
 
     #[test]
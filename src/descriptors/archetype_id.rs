@@ -1,37 +1,66 @@
+use crate::constants::RawArchetypeId;
+
 /// Represents the unique subset of components as a comparable identifier.
 /// See [`ArchetypeDescriptor::compute_archetype_id`] for computing an instance of it.
-/// The invalid archetype id is defined to be 0.
+/// The invalid archetype id is defined to be the maximum value.
+/// Is 32 bits wide by default, 64 bits wide with the `wide_archetype_ids` feature,
+/// or 128 bits wide with the `extra_wide_archetype_ids` feature.
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct ArchetypeId(u32);
+pub struct ArchetypeId(RawArchetypeId);
 
 impl From<u32> for ArchetypeId {
     fn from(v: u32) -> Self {
-        Self(v)
+        Self::from_u32(v)
     }
 }
 
 impl ArchetypeId {
-    /// The invalid archetype id, defined to be zero.
+    /// The invalid archetype id, defined to be the maximum value.
     /// Any archetype with this id must not be used as valid archetype.
-    pub const INVALID: ArchetypeId = ArchetypeId::from_u32(u32::MAX);
+    pub const INVALID: ArchetypeId = ArchetypeId::from_raw(RawArchetypeId::MAX);
+    /// The archetype id of the empty archetype, which contains no components.
+    pub const EMPTY: ArchetypeId = ArchetypeId::from_raw(RawArchetypeId::MAX - 1);
     /// Returns true if the archetype id is valid.
     pub const fn is_valid(&self) -> bool {
         self.0 != Self::INVALID.0
     }
     /// Construct an archetype id from a u32.
+    #[allow(clippy::unnecessary_cast)] // The raw id is a u32 without wide archetype ids.
     pub const fn from_u32(v: u32) -> Self {
-        ArchetypeId(v)
+        ArchetypeId(v as RawArchetypeId)
     }
     /// Construct a u32 from an archetype id.
+    #[cfg(not(any(feature = "wide_archetype_ids", feature = "extra_wide_archetype_ids")))]
     pub const fn into_u32(self) -> u32 {
         self.0
     }
+    /// Construct an archetype id from its raw value.
+    pub const fn from_raw(v: RawArchetypeId) -> Self {
+        ArchetypeId(v)
+    }
+    /// Returns the raw value of the archetype id.
+    pub const fn into_raw(self) -> RawArchetypeId {
+        self.0
+    }
+    /// Computes an archetype id by hashing the given bytes.
+    pub(crate) const fn from_hash(bytes: &[u8]) -> Self {
+        #[cfg(not(any(feature = "wide_archetype_ids", feature = "extra_wide_archetype_ids")))]
+        let hash = crate::fnv1a::fnv1a_hash_32(bytes, Some(bytes.len()));
+        #[cfg(all(
+            feature = "wide_archetype_ids",
+            not(feature = "extra_wide_archetype_ids")
+        ))]
+        let hash = crate::fnv1a::fnv1a_hash_64(bytes, Some(bytes.len()));
+        #[cfg(feature = "extra_wide_archetype_ids")]
+        let hash = crate::fnv1a::fnv1a_hash_128(bytes, Some(bytes.len()));
+        ArchetypeId(hash)
+    }
 }
 
 impl ArchetypeId {
     /// Copies the value into an array of ne_bytes. (See [`u32::to_ne_bytes`]).
-    pub const fn to_ne_bytes(self) -> [u8; 4] {
+    pub const fn to_ne_bytes(self) -> [u8; core::mem::size_of::<RawArchetypeId>()] {
         self.0.to_ne_bytes()
     }
 }
@@ -1,18 +1,295 @@
+use crate::define_component_descriptor;
 use crate::descriptors::component_descriptor::*;
 use crate::descriptors::component_type_id::ComponentTypeId;
-use crate::{define_component_descriptor, fnv1a::fnv1a_hash_str_16_xor};
+use alloc::string::String;
 
 /// Implement this trait to use a type as a component in the ECS.
 /// Do not override the default implementations for [`Self::ID`] or [`Self::DESCRIPTOR`].
 /// Only implement [`Self::NAME`].
-/// # Safety:
-/// - size_of<Self> must not exceed u16::MAX.
-/// - align_of<Self> must not exceed u16::MAX.
+/// The size and alignment of `Self` must not exceed u16::MAX, which is checked at compile time:
+/// ```compile_fail
+/// use shard_ecs::*;
+///
+/// struct Oversized([u8; 65536]);
+/// impl Component for Oversized {
+///     const NAME: &'static str = "Oversized";
+/// }
+///
+/// let mut registry = Registry::default();
+/// let _ = registry.create_entity(Oversized([0; 65536]));
+/// ```
 pub trait Component: Send + Sync + Sized + 'static {
     /// Human readable program unique name used for calculating a stable type identifier.
     const NAME: &'static str;
     /// Do not implement this manually. (Unless a hash collision occurs).
-    const ID: ComponentTypeId = ComponentTypeId::from_u16(fnv1a_hash_str_16_xor(Self::NAME));
+    /// Registries report distinct component types sharing an id the first time both are seen.
+    const ID: ComponentTypeId = ComponentTypeId::from_name(Self::NAME);
     /// A descriptor defining the component type.
     const DESCRIPTOR: ComponentDescriptor = define_component_descriptor!(Self);
 }
+
+/// Provides a stable name for types used as generic arguments of components deriving [`Component`].
+/// The names of the generic arguments are part of the [`Component::NAME`] of the derived component,
+/// so that e.g. `Foo<u32>` and `Foo<f32>` are distinct component types.
+/// Implemented for primitive types, strings, arrays, tuples, options and all components.
+pub trait TypeArgumentName: 'static {
+    /// Program unique name of the type.
+    const TYPE_ARGUMENT_NAME: &'static str;
+}
+
+impl<C: Component> TypeArgumentName for C {
+    const TYPE_ARGUMENT_NAME: &'static str = C::NAME;
+}
+
+macro_rules! impl_type_argument_name {
+    ($($ty:ty),*) => {
+        $(
+            impl TypeArgumentName for $ty {
+                const TYPE_ARGUMENT_NAME: &'static str = stringify!($ty);
+            }
+        )*
+    };
+}
+
+impl_type_argument_name!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str,
+    String
+);
+
+impl<T: TypeArgumentName> TypeArgumentName for Option<T> {
+    const TYPE_ARGUMENT_NAME: &'static str =
+        TypeName::with_arguments("Option", &[T::TYPE_ARGUMENT_NAME]).as_str();
+}
+
+impl<T: TypeArgumentName, const N: usize> TypeArgumentName for [T; N] {
+    const TYPE_ARGUMENT_NAME: &'static str = TypeName::new()
+        .push("[")
+        .push(T::TYPE_ARGUMENT_NAME)
+        .push("; ")
+        .push_integer(N as i128)
+        .push("]")
+        .as_str();
+}
+
+macro_rules! impl_type_argument_name_tuple {
+    ($($elem:ident), *) => {
+        impl<$($elem: TypeArgumentName),*> TypeArgumentName for ($($elem,)*) {
+            const TYPE_ARGUMENT_NAME: &'static str =
+                TypeName::with_arguments("", &[$($elem::TYPE_ARGUMENT_NAME),*]).as_str();
+        }
+    };
+}
+
+impl_type_argument_name_tuple!(T1);
+impl_type_argument_name_tuple!(T1, T2);
+impl_type_argument_name_tuple!(T1, T2, T3);
+impl_type_argument_name_tuple!(T1, T2, T3, T4);
+impl_type_argument_name_tuple!(T1, T2, T3, T4, T5);
+impl_type_argument_name_tuple!(T1, T2, T3, T4, T5, T6);
+impl_type_argument_name_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_type_argument_name_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// Builds type names at compile time, such as the names of generic components.
+/// Used by the derive macros, do not use this manually.
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct TypeName {
+    bytes: [u8; TypeName::CAPACITY],
+    len: usize,
+}
+
+impl TypeName {
+    /// The maximum length of a type name in bytes.
+    pub const CAPACITY: usize = 256;
+
+    const fn new() -> Self {
+        Self {
+            bytes: [0; Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Returns the name followed by the given arguments, e.g. `Foo<u32, f32>`.
+    /// An empty name results in a tuple, e.g. `(u32, f32)`.
+    pub const fn with_arguments(name: &str, arguments: &[&str]) -> Self {
+        let tuple = name.is_empty();
+        let mut v = Self::new().push(name).push(if tuple { "(" } else { "<" });
+        let mut i = 0;
+        while i < arguments.len() {
+            if i > 0 {
+                v = v.push(", ");
+            }
+            v = v.push(arguments[i]);
+            i += 1;
+        }
+        if tuple && arguments.len() == 1 {
+            v = v.push(",");
+        }
+        v.push(if tuple { ")" } else { ">" })
+    }
+
+    /// Appends the given string.
+    /// Panics at compile time if the name exceeds [`TypeName::CAPACITY`].
+    pub const fn push(mut self, value: &str) -> Self {
+        let bytes = value.as_bytes();
+        assert!(
+            self.len + bytes.len() <= Self::CAPACITY,
+            "The type name is too long."
+        );
+        let mut i = 0;
+        while i < bytes.len() {
+            self.bytes[self.len] = bytes[i];
+            self.len += 1;
+            i += 1;
+        }
+        self
+    }
+
+    /// Returns the given integer in decimal notation, as used for const generic arguments.
+    pub const fn from_integer(value: i128) -> Self {
+        Self::new().push_integer(value)
+    }
+
+    /// Appends the given integer in decimal notation.
+    pub const fn push_integer(self, value: i128) -> Self {
+        let mut digits = [0; 40];
+        let mut start = digits.len();
+        let mut rest = value.unsigned_abs();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        if value < 0 {
+            start -= 1;
+            digits[start] = b'-';
+        }
+        // Only contains ASCII digits and the sign.
+        self.push(unsafe { core::str::from_utf8_unchecked(digits.split_at(start).1) })
+    }
+
+    pub const fn as_str(&self) -> &str {
+        // Only whole strings are appended, so the bytes are valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(self.bytes.split_at(self.len).0) }
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::{Component, ComponentTypeId};
+    use alloc::string::String;
+
+    mod first {
+        #[derive(crate::Component)]
+        pub struct Position;
+    }
+
+    mod second {
+        #[derive(crate::Component)]
+        pub struct Position;
+    }
+
+    #[derive(Component)]
+    struct Wrapper<T: Send + Sync>(T);
+
+    #[derive(Component)]
+    struct Buffer<const N: usize>([u8; N]);
+
+    #[derive(Component)]
+    struct Pair<T: Send + Sync, U: Send + Sync>(T, U);
+
+    #[derive(Component)]
+    #[shard(name = "Renamed")]
+    struct Named;
+
+    #[derive(Component)]
+    #[shard(name = "RenamedWrapper")]
+    struct NamedWrapper<T: Send + Sync>(T);
+
+    #[derive(Component)]
+    #[shard(id = 7)]
+    struct Fixed;
+
+    #[test]
+    fn test_derive_component() {
+        assert_eq!(
+            <first::Position as Component>::NAME,
+            concat!(module_path!(), "::first::Position")
+        );
+        assert_ne!(
+            <first::Position as Component>::ID,
+            <second::Position as Component>::ID
+        );
+        assert_ne!(
+            <Wrapper<u32> as Component>::ID,
+            <Wrapper<f32> as Component>::ID
+        );
+        assert_ne!(
+            <Wrapper<Wrapper<u32>> as Component>::ID,
+            <Wrapper<Wrapper<f32>> as Component>::ID
+        );
+        assert_ne!(<Buffer<4> as Component>::ID, <Buffer<8> as Component>::ID);
+        assert_eq!(
+            <Wrapper<u32> as Component>::NAME,
+            concat!(module_path!(), "::Wrapper<u32>")
+        );
+        assert_eq!(
+            <Wrapper<u32> as Component>::ID,
+            ComponentTypeId::from_name(<Wrapper<u32> as Component>::NAME)
+        );
+        assert_eq!(
+            <Wrapper<Wrapper<f32>> as Component>::NAME,
+            concat!(
+                module_path!(),
+                "::Wrapper<",
+                module_path!(),
+                "::Wrapper<f32>>"
+            )
+        );
+        assert_eq!(
+            <Buffer<4> as Component>::NAME,
+            concat!(module_path!(), "::Buffer<4>")
+        );
+        assert_eq!(
+            <Pair<[Option<u8>; 3], (String, &'static str, (i32,))> as Component>::NAME,
+            concat!(
+                module_path!(),
+                "::Pair<[Option<u8>; 3], (String, &'static str, (i32,))>"
+            )
+        );
+        assert_eq!(
+            <Pair<(), (u16, u16)> as Component>::NAME,
+            concat!(module_path!(), "::Pair<(), (u16, u16)>")
+        );
+        assert_eq!(
+            <NamedWrapper<i64> as Component>::NAME,
+            "RenamedWrapper<i64>"
+        );
+        assert_eq!(<Named as Component>::NAME, "Renamed");
+        assert_eq!(
+            <Named as Component>::ID,
+            ComponentTypeId::from_name("Renamed")
+        );
+        assert_eq!(<Fixed as Component>::ID, ComponentTypeId::from_raw(7));
+    }
+}
@@ -9,8 +9,21 @@ macro_rules! define_component_descriptor {
     ($item:ident) => {
         ComponentDescriptor {
             component_type_id: $item::ID,
-            size: core::mem::size_of::<$item>() as u16,
-            align: core::mem::align_of::<$item>() as u16,
+            name: $item::NAME,
+            size: {
+                assert!(
+                    core::mem::size_of::<$item>() <= u16::MAX as usize,
+                    "The size of a component must not exceed u16::MAX."
+                );
+                core::mem::size_of::<$item>() as u16
+            },
+            align: {
+                assert!(
+                    core::mem::align_of::<$item>() <= u16::MAX as usize,
+                    "The alignment of a component must not exceed u16::MAX."
+                );
+                core::mem::align_of::<$item>() as u16
+            },
             fns: ComponentDescriptorFnPointers {
                 drop_handler: ComponentDescriptor::drop_handler_wrapper::<$item>,
             },
@@ -22,6 +35,7 @@ macro_rules! define_component_descriptor {
 macro_rules! copy_component_descriptor_from_to {
     ($source:expr, $destination:expr) => {
         $destination.component_type_id = $source.component_type_id;
+        $destination.name = $source.name;
         $destination.size = $source.size;
         $destination.align = $source.align;
         $destination.fns = $source.fns;
@@ -35,12 +49,13 @@ pub struct ComponentDescriptorFnPointers {
 }
 
 /// Describes a specific component type.
-/// # Safety:
+/// # Safety
 /// - [`size`] must not exceed [`u16::MAX`].
 /// - [`align`] must not exceed [`u16::MAX`].
 #[derive(Debug, Clone, PartialEq)]
 pub struct ComponentDescriptor {
     pub component_type_id: ComponentTypeId,
+    pub name: &'static str,
     pub size: u16,
     pub align: u16,
     pub fns: ComponentDescriptorFnPointers,
@@ -103,6 +118,7 @@ impl ComponentDescriptor {
         unsafe fn _dummy_drop_(_ptr: *mut u8, _len: usize) {}
         ComponentDescriptor {
             component_type_id: ComponentTypeId::INVALID,
+            name: "",
             size: 0,
             align: 0,
             fns: ComponentDescriptorFnPointers {
@@ -115,6 +131,7 @@ impl ComponentDescriptor {
     /// Returns [`ComponentDescriptor::INVALID`] if a valid descriptor cannot be constructed.
     pub fn new(
         component_type_id: ComponentTypeId,
+        name: &'static str,
         size: u16,
         align: u16,
         drop_handler: unsafe fn(ptr: *mut u8, len: usize),
@@ -125,6 +142,7 @@ impl ComponentDescriptor {
 
         Self {
             component_type_id,
+            name,
             size,
             align,
             fns: ComponentDescriptorFnPointers { drop_handler },
@@ -144,6 +162,11 @@ impl ComponentDescriptor {
         self.component_type_id
     }
 
+    /// Get a the component descriptor's name, as defined by [`Component::NAME`].
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
     /// Get a the component descriptor's size.
     pub const fn size(&self) -> u16 {
         self.size
@@ -153,4 +176,29 @@ impl ComponentDescriptor {
     pub const fn align(&self) -> u16 {
         self.align
     }
+
+    /// Returns whether both descriptors describe the same component type.
+    /// Component type ids are hashes, so the name and layout are compared as well.
+    /// Panics in debug builds if distinct component types share the component type id, naming both of them.
+    pub fn is_same_component_type(&self, descriptor: &ComponentDescriptor) -> bool {
+        if self.component_type_id != descriptor.component_type_id {
+            return false;
+        }
+        let same = same_component_name(self.name, descriptor.name)
+            && self.size == descriptor.size
+            && self.align == descriptor.align;
+        if !same && cfg!(debug_assertions) {
+            panic!(
+                "Component types `{}` and `{}` share the same component type id.",
+                self.name, descriptor.name
+            );
+        }
+        same
+    }
+}
+
+/// Compares two component type names.
+/// Names of the same component type usually share their address, which skips comparing the strings.
+pub(crate) fn same_component_name(a: &'static str, b: &'static str) -> bool {
+    core::ptr::eq(a, b) || a == b
 }
@@ -4,7 +4,6 @@ use crate::descriptors::component_descriptor::{
 use crate::{define_component_descriptor, Component, MAX_COMPONENTS_PER_ENTITY};
 
 use crate::descriptors::component_group_descriptor::ComponentGroupDescriptor;
-use private::SealedComponentGroup;
 
 #[macro_export]
 macro_rules! expr {
@@ -21,17 +20,22 @@ macro_rules! tuple_index {
 }
 
 /// Represents a group of components. Used for specifying which component types should be matched in query's.
-pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
+/// Implemented for components and tuples of components, use `#[derive(Bundle)]` to implement it for structs.
+/// # Safety
+/// - [`Self::DESCRIPTOR`] must describe exactly the components of the group.
+/// - The pointer based functions must only access the components described by [`Self::DESCRIPTOR`],
+///   at the sorted positions given by it.
+pub unsafe trait ComponentGroup: Sized + 'static {
     type RefTuple<'c>: 'c
     where
         Self: 'c;
     type MutRefTuple<'c>: 'c
     where
         Self: 'c;
-    type SliceRefTuple<'c>: 'c
+    type SliceRefTuple<'c>: 'c + Send
     where
         Self: 'c;
-    type SliceMutRefTuple<'c>: 'c
+    type SliceMutRefTuple<'c>: 'c + Send
     where
         Self: 'c;
 
@@ -55,7 +59,7 @@ pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
     ) -> Self::MutRefTuple<'b>;
 
     /// Returns a tuple of slices extracted from the given pointers.
-    /// # Safety:
+    /// # Safety
     /// - The pointers must be sorted.
     unsafe fn slice_unchecked<'a, 'b>(
         sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
@@ -63,13 +67,37 @@ pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
     ) -> Self::SliceRefTuple<'b>;
 
     /// Returns a tuple of mutable slices extracted from the given pointers.
-    /// # Safety:
+    /// # Safety
     /// - The pointers must be sorted.
     unsafe fn slice_unchecked_mut<'a, 'b>(
         sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
         len: usize,
     ) -> Self::SliceMutRefTuple<'b>;
 
+    /// Returns a reference tuple of the components at the given index in a tuple of slices.
+    /// # Safety
+    /// - Index must be smaller than the length of the slices.
+    unsafe fn slice_get_unchecked<'a>(
+        slices: &Self::SliceRefTuple<'a>,
+        index: usize,
+    ) -> Self::RefTuple<'a>;
+
+    /// Returns a mutable reference tuple of the components at the given index in a tuple of mutable slices.
+    /// # Safety
+    /// - Index must be smaller than the length of the slices.
+    /// - The returned references must not alias other references into the slices.
+    unsafe fn slice_get_unchecked_mut<'a>(
+        slices: &mut Self::SliceMutRefTuple<'a>,
+        index: usize,
+    ) -> Self::MutRefTuple<'a>;
+
+    /// Splits a tuple of mutable slices into two at the given index.
+    /// Panics if index is larger than the length of the slices.
+    fn split_slices_at_mut<'a>(
+        slices: Self::SliceMutRefTuple<'a>,
+        index: usize,
+    ) -> (Self::SliceMutRefTuple<'a>, Self::SliceMutRefTuple<'a>);
+
     /// Returns an tuple of empty slices.
     fn empty_slice<'a>() -> Self::SliceRefTuple<'a>;
 
@@ -77,7 +105,7 @@ pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
     fn empty_slice_mut<'a>() -> Self::SliceMutRefTuple<'a>;
 }
 
-impl<T: Component + SealedComponentGroup> ComponentGroup for T {
+unsafe impl<T: Component> ComponentGroup for T {
     type RefTuple<'c> = &'c T;
     type MutRefTuple<'c> = &'c mut T;
 
@@ -121,6 +149,27 @@ impl<T: Component + SealedComponentGroup> ComponentGroup for T {
         core::slice::from_raw_parts_mut(sorted_pointers[0] as *mut T, len)
     }
 
+    unsafe fn slice_get_unchecked<'a>(
+        slices: &Self::SliceRefTuple<'a>,
+        index: usize,
+    ) -> Self::RefTuple<'a> {
+        &*slices.as_ptr().add(index)
+    }
+
+    unsafe fn slice_get_unchecked_mut<'a>(
+        slices: &mut Self::SliceMutRefTuple<'a>,
+        index: usize,
+    ) -> Self::MutRefTuple<'a> {
+        &mut *slices.as_mut_ptr().add(index)
+    }
+
+    fn split_slices_at_mut<'a>(
+        slices: Self::SliceMutRefTuple<'a>,
+        index: usize,
+    ) -> (Self::SliceMutRefTuple<'a>, Self::SliceMutRefTuple<'a>) {
+        slices.split_at_mut(index)
+    }
+
     fn empty_slice<'a>() -> Self::SliceRefTuple<'a> {
         &[]
     }
@@ -132,8 +181,8 @@ impl<T: Component + SealedComponentGroup> ComponentGroup for T {
 
 macro_rules! impl_component_tuple {
     ($len:expr, $(($elem:ident, $elem_idx:tt)), *) => {
-        impl<$($elem),*> ComponentGroup for ($($elem), *)
-        where $( $elem : Component + SealedComponentGroup ),*, Self : SealedComponentGroup
+        unsafe impl<$($elem),*> ComponentGroup for ($($elem), *)
+        where $( $elem : Component ),*
         {
             type RefTuple<'s> = ($(&'s $elem),*);
             type MutRefTuple<'s> = ($(&'s mut $elem),*);
@@ -141,6 +190,34 @@ macro_rules! impl_component_tuple {
             type SliceRefTuple<'s> = ($(&'s [$elem]),*);
             type SliceMutRefTuple<'s> = ($(&'s mut [$elem]),*);
 
+            unsafe fn slice_get_unchecked<'a>(
+                slices: &Self::SliceRefTuple<'a>,
+                index: usize,
+            ) -> Self::RefTuple<'a> {
+                ($(
+                    &*tuple_index!(slices, $elem_idx).as_ptr().add(index),
+                )*)
+            }
+
+            unsafe fn slice_get_unchecked_mut<'a>(
+                slices: &mut Self::SliceMutRefTuple<'a>,
+                index: usize,
+            ) -> Self::MutRefTuple<'a> {
+                ($(
+                    &mut *tuple_index!(slices, $elem_idx).as_mut_ptr().add(index),
+                )*)
+            }
+
+            fn split_slices_at_mut<'a>(
+                slices: Self::SliceMutRefTuple<'a>,
+                index: usize,
+            ) -> (Self::SliceMutRefTuple<'a>, Self::SliceMutRefTuple<'a>) {
+                let split = ($(
+                    tuple_index!(slices, $elem_idx).split_at_mut(index),
+                )*);
+                (($(tuple_index!(split, $elem_idx).0), *), ($(tuple_index!(split, $elem_idx).1), *))
+            }
+
             fn empty_slice<'a>() -> Self::SliceRefTuple<'a> {
                 ($(&[] as &[$elem]), *)
             }
@@ -514,32 +591,47 @@ impl_component_tuple!(4, (T1, 0), (T2, 1), (T3, 2), (T4, 3));
 impl_component_tuple!(3, (T1, 0), (T2, 1), (T3, 2));
 impl_component_tuple!(2, (T1, 0), (T2, 1));
 
-mod private {
-    use crate::Component;
-
-    pub trait SealedComponentGroup {}
-
-    impl<T> SealedComponentGroup for T where T: Component {}
-
-    macro_rules! impl_sealed_component_tuples {
-        ($($ty:ident)*) => {}; //base case
-        ($head:ident, $($tail:ident),*) => {
-            impl<$($tail),*, $head> SealedComponentGroup for impl_sealed_component_tuples!([$($tail)*] $head)
-            where $head : Component, $( $tail : Component ),* {}
-
-            impl_sealed_component_tuples!($($tail),*);
-        };
-        ([] $($ty:ident)*) => {
-            ($($ty), *)
-        };
-        ([$first:ident $($tail:ident)*] $($ty:ident)*) => {
-            impl_sealed_component_tuples!([$($tail)*] $first $($ty)*)
-        };
+/// Returns the sorted pointers of the subgroup `S`, given the sorted pointers of the group `G` containing it.
+/// The components of `S` start at `offset` within the groups passed to [`ComponentGroupDescriptor::from_groups`].
+/// Used by `#[derive(Bundle)]` to forward to the component groups of its fields.
+#[doc(hidden)]
+pub fn subgroup_pointers<G: ComponentGroup, S: ComponentGroup>(
+    sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+    offset: usize,
+) -> [*mut u8; MAX_COMPONENTS_PER_ENTITY] {
+    let mut pointers = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+    for (index, pointer) in pointers
+        .iter_mut()
+        .enumerate()
+        .take(S::DESCRIPTOR.len() as usize)
+    {
+        *pointer =
+            sorted_pointers[G::DESCRIPTOR.unsorted_to_sorted((offset + index) as u8) as usize];
     }
+    pointers
+}
 
-    impl_sealed_component_tuples!(
-        T16, T15, T14, T13, T12, T11, T10, T9, T8, T7, T6, T5, T4, T3, T2, T1
-    );
+/// Writes the sorted pointers of `subgroup` into the sorted pointers of the group `G` containing it.
+/// The components of `S` start at `offset` within the groups passed to [`ComponentGroupDescriptor::from_groups`].
+/// Used by `#[derive(Bundle)]` to forward to the component groups of its fields.
+/// # Safety
+/// - See [`ComponentGroup::as_sorted_pointers`].
+#[doc(hidden)]
+pub unsafe fn write_subgroup_pointers<G: ComponentGroup, S: ComponentGroup>(
+    subgroup: &mut S,
+    sorted_pointers: &mut [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+    offset: usize,
+) {
+    let mut pointers = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+    subgroup.as_sorted_pointers(&mut pointers);
+    for (index, pointer) in pointers
+        .iter()
+        .enumerate()
+        .take(S::DESCRIPTOR.len() as usize)
+    {
+        sorted_pointers[G::DESCRIPTOR.unsorted_to_sorted((offset + index) as u8) as usize] =
+            *pointer;
+    }
 }
 
 #[cfg(test)]
@@ -576,7 +668,18 @@ mod tests {
         extern crate std;
 
         assert!(<Position as ComponentGroup>::DESCRIPTOR.is_valid());
-        assert!(!<(Position, Position) as ComponentGroup>::DESCRIPTOR.is_valid());
         assert!(<(Position, Rotation) as ComponentGroup>::DESCRIPTOR.is_valid());
     }
+
+    #[test]
+    #[should_panic(
+        expected = "A component group must not contain the same component type more than once."
+    )]
+    fn test_component_group_descriptor_duplicates() {
+        // Evaluated at runtime here, used as constant this fails to compile instead.
+        ComponentGroupDescriptor::new(&[
+            <Position as Component>::DESCRIPTOR,
+            <Position as Component>::DESCRIPTOR,
+        ]);
+    }
 }
@@ -53,75 +53,118 @@ impl ComponentGroupDescriptor {
 }
 
 impl ComponentGroupDescriptor {
+    /// Creates the descriptor of a group consisting of the given components.
+    /// Panics if the group is empty, too large or contains a component type more than once.
+    /// As component group descriptors are constants, this rejects such groups at compile time:
+    /// ```compile_fail
+    /// use shard_ecs::*;
+    ///
+    /// struct Position(f32, f32);
+    /// impl Component for Position {
+    ///     const NAME: &'static str = "Position";
+    /// }
+    ///
+    /// let mut registry = Registry::default();
+    /// let _ = registry.create_entity((Position(0.0, 0.0), Position(1.0, 1.0)));
+    /// ```
     pub const fn new<const N: usize>(descriptors: &[ComponentDescriptor; N]) -> Self {
-        if !Self::validate_component_descriptors(descriptors) {
-            return Self::INVALID;
+        Self::from_unsorted_descriptors(descriptors)
+    }
+
+    /// Creates the descriptor of a group consisting of all components of the given groups.
+    /// Used for bundles, which flatten their (nested) fields into a single component group.
+    /// The sorted components of each group are concatenated in order, so [`Self::unsorted_to_sorted`]
+    /// maps the components of a group, offset by the length of the preceding groups, into the whole group.
+    pub const fn from_groups(groups: &[&ComponentGroupDescriptor]) -> Self {
+        let mut descriptors = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
+        let mut len = 0;
+        let mut i = 0;
+        while i < groups.len() {
+            let components = unsafe { groups[i].archetype.components_unchecked() };
+            let mut j = 0;
+            while j < groups[i].len() as usize {
+                assert!(
+                    len < MAX_COMPONENTS_PER_ENTITY,
+                    "A component group must not exceed MAX_COMPONENTS_PER_ENTITY components."
+                );
+                copy_component_descriptor_from_to!(components[j], descriptors[len]);
+                len += 1;
+                j += 1;
+            }
+            i += 1;
         }
+        Self::from_unsorted_descriptors(descriptors.split_at(len).0)
+    }
+
+    const fn from_unsorted_descriptors(descriptors: &[ComponentDescriptor]) -> Self {
+        Self::validate_component_descriptors(descriptors);
+        let len = descriptors.len();
 
         let sorted_descriptors = Self::compute_sorted_descriptors(descriptors);
 
-        let id = ArchetypeDescriptor::compute_archetype_id(&sorted_descriptors);
-        if !id.is_valid() {
-            return Self::INVALID;
-        }
+        // Only the first len descriptors are valid, the remainder must not influence the id.
+        let id = ArchetypeDescriptor::compute_archetype_id(sorted_descriptors.split_at(len).0);
+        assert!(
+            id.is_valid(),
+            "The archetype id of a component group must not be the invalid id, override the ID of one of its components."
+        );
 
         let (unsorted_to_sorted, sorted_to_unsorted) =
             ComponentGroupDescriptor::compute_sort_mappings(descriptors, &sorted_descriptors);
 
-        let value = Self {
-            archetype: ArchetypeDescriptor::new(id, N as u8, sorted_descriptors),
+        Self {
+            archetype: ArchetypeDescriptor::new(id, len as u8, sorted_descriptors),
             sorted_to_unsorted,
             unsorted_to_sorted,
-        };
-        if !value.archetype.archetype_id().is_valid() {
-            return Self::INVALID;
         }
-        value
     }
 
-    const fn validate_component_descriptors<const N: usize>(
-        descriptors: &[ComponentDescriptor; N],
-    ) -> bool {
+    const fn validate_component_descriptors(descriptors: &[ComponentDescriptor]) {
         // Length may not be zero or larger than max components.
-        if descriptors.is_empty() || descriptors.len() > MAX_COMPONENTS_PER_ENTITY {
-            return false;
-        }
+        assert!(
+            !descriptors.is_empty(),
+            "A component group must contain at least one component."
+        );
+        assert!(
+            descriptors.len() <= MAX_COMPONENTS_PER_ENTITY,
+            "A component group must not exceed MAX_COMPONENTS_PER_ENTITY components."
+        );
         // Duplicates MUST not exist.
         let mut idx = 0;
-        while idx < N {
+        while idx < descriptors.len() {
             let mut cdx = idx + 1;
-            while cdx < N {
-                if descriptors[idx].component_type_id().into_u16()
-                    == descriptors[cdx].component_type_id().into_u16()
-                {
-                    return false;
-                }
+            while cdx < descriptors.len() {
+                assert!(
+                    descriptors[idx].component_type_id().into_raw()
+                        != descriptors[cdx].component_type_id().into_raw(),
+                    "A component group must not contain the same component type more than once."
+                );
                 cdx += 1;
             }
             idx += 1;
         }
-        true
     }
 
     /// Computes the sorted version of a given array of descriptors.
     /// # Warning: only functions correctly if descriptors passed in are correctly validated.
-    /// # Similarly, N must be smaller or equal to [`MAX_COMPONENTS_PER_ENTITY`].
-    const fn compute_sorted_descriptors<const N: usize>(
-        descriptors: &[ComponentDescriptor; N],
+    /// # Similarly, the length must be smaller or equal to [`MAX_COMPONENTS_PER_ENTITY`].
+    const fn compute_sorted_descriptors(
+        descriptors: &[ComponentDescriptor],
     ) -> [ComponentDescriptor; MAX_COMPONENTS_PER_ENTITY] {
+        let n = descriptors.len();
         let mut return_value = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
         let mut i = 0;
-        while i < N {
+        while i < n {
             copy_component_descriptor_from_to!(descriptors[i], return_value[i]);
             i += 1;
         }
         i = 0;
 
-        while i < N {
+        while i < n {
             let mut j = i + 1;
-            while j < N {
-                if return_value[j].component_type_id.into_u16()
-                    < return_value[i].component_type_id.into_u16()
+            while j < n {
+                if return_value[j].component_type_id.into_raw()
+                    < return_value[i].component_type_id.into_raw()
                 {
                     let mut temp = ComponentDescriptor::INVALID;
                     copy_component_descriptor_from_to!(return_value[i], temp);
@@ -137,9 +180,9 @@ impl ComponentGroupDescriptor {
     }
 
     /// Computes the mappings from sorted to unsorted and from unsorted to sorted.
-    /// # Warning: MUST be used on valid mappings and N must be less than or equal to [`MAX_COMPONENTS_PER_ENTITY`].
-    const fn compute_sort_mappings<const N: usize>(
-        unsorted: &[ComponentDescriptor; N],
+    /// # Warning: MUST be used on valid mappings and the length must be less than or equal to [`MAX_COMPONENTS_PER_ENTITY`].
+    const fn compute_sort_mappings(
+        unsorted: &[ComponentDescriptor],
         sorted: &[ComponentDescriptor; MAX_COMPONENTS_PER_ENTITY],
     ) -> (
         [u8; MAX_COMPONENTS_PER_ENTITY],
@@ -148,17 +191,18 @@ impl ComponentGroupDescriptor {
         let mut unsorted_to_sorted = [0; MAX_COMPONENTS_PER_ENTITY];
         let mut sorted_to_unsorted = [0; MAX_COMPONENTS_PER_ENTITY];
 
+        let n = unsorted.len();
         let mut i = 0;
-        while i < N {
+        while i < n {
             let mut j = 0;
-            while j < N {
-                if sorted[j].component_type_id.into_u16()
-                    == unsorted[i].component_type_id.into_u16()
+            while j < n {
+                if sorted[j].component_type_id.into_raw()
+                    == unsorted[i].component_type_id.into_raw()
                 {
                     unsorted_to_sorted[i] = j as u8;
                 }
-                if unsorted[j].component_type_id.into_u16()
-                    == sorted[i].component_type_id.into_u16()
+                if unsorted[j].component_type_id.into_raw()
+                    == sorted[i].component_type_id.into_raw()
                 {
                     sorted_to_unsorted[i] = j as u8;
                 }
@@ -1,40 +1,65 @@
+use crate::constants::{RawArchetypeId, RawComponentTypeId};
 use crate::ArchetypeId;
 
 /// Represents the type of a Component as an identifier.
+/// Is 16 bits wide by default, or 64 bits wide with the `wide_component_ids` feature.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
-pub struct ComponentTypeId(u16);
+pub struct ComponentTypeId(RawComponentTypeId);
 
 impl From<u16> for ComponentTypeId {
     fn from(v: u16) -> Self {
-        Self(v)
+        Self::from_u16(v)
     }
 }
 
 impl From<ComponentTypeId> for ArchetypeId {
+    #[allow(clippy::unnecessary_cast)] // The raw ids have the same width with some features.
     fn from(value: ComponentTypeId) -> Self {
-        ArchetypeId::from_u32(value.0 as u32)
+        ArchetypeId::from_raw(value.0 as RawArchetypeId)
     }
 }
 
 impl ComponentTypeId {
-    pub const INVALID: ComponentTypeId = ComponentTypeId::from_u16(u16::MAX);
+    pub const INVALID: ComponentTypeId = ComponentTypeId::from_raw(RawComponentTypeId::MAX);
 
     pub const fn is_valid(&self) -> bool {
         self.0 != Self::INVALID.0
     }
 
+    /// Computes the component type id from the name of a component type.
+    #[cfg(not(feature = "wide_component_ids"))]
+    pub const fn from_name(name: &str) -> Self {
+        Self(crate::fnv1a::fnv1a_hash_str_16_xor(name))
+    }
+
+    /// Computes the component type id from the name of a component type.
+    #[cfg(feature = "wide_component_ids")]
+    pub const fn from_name(name: &str) -> Self {
+        Self(crate::fnv1a::fnv1a_hash_str_64(name))
+    }
+
+    #[allow(clippy::unnecessary_cast)] // The raw id is a u16 without wide component ids.
     pub const fn from_u16(v: u16) -> Self {
-        Self(v)
+        Self(v as RawComponentTypeId)
     }
 
+    #[cfg(not(feature = "wide_component_ids"))]
     pub const fn into_u16(self) -> u16 {
         self.0
     }
+
+    pub const fn from_raw(v: RawComponentTypeId) -> Self {
+        Self(v)
+    }
+
+    pub const fn into_raw(self) -> RawComponentTypeId {
+        self.0
+    }
 }
 
 impl ComponentTypeId {
-    pub const fn to_ne_bytes(self) -> [u8; 2] {
+    pub const fn to_ne_bytes(self) -> [u8; core::mem::size_of::<RawComponentTypeId>()] {
         self.0.to_ne_bytes()
     }
 }
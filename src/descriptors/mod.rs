@@ -7,7 +7,7 @@ pub mod component_group_descriptor;
 pub mod component_type_id;
 
 pub use archetype_id::*;
-pub use component::Component;
+pub use component::{Component, TypeArgumentName, TypeName};
 pub use component_descriptor::ComponentDescriptor;
 pub use component_group::ComponentGroup;
 pub use component_group_descriptor::ComponentGroupDescriptor;
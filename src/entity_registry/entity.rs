@@ -4,7 +4,7 @@ use crate::constants::*;
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Entity {
-    handle: u32,
+    handle: RawEntityHandle,
 }
 
 impl Default for Entity {
@@ -17,12 +17,12 @@ impl Entity {
     pub const INVALID: Entity =
         unsafe { Entity::new_unchecked(MAX_ENTITY_HANDLE_VALUE, MAX_ENTITY_VERSION_VALUE) };
 
-    /// Constructs a new Entity from a raw u32.
-    /// The higher/leftmost 24 bits as index, the lower/rightmost 8 bits are used as version.
+    /// Constructs a new Entity from a raw handle.
+    /// The higher/leftmost [`ENTITY_HANDLE_BITS`] bits as index, the lower/rightmost [`ENTITY_VERSION_BITS`] bits are used as version.
     /// # Safety
     /// Keep in mind the bits are interpreted as defined by the entity handle layout.
     /// These must be respected by the provided integer value.
-    pub const unsafe fn from_raw(raw: u32) -> Entity {
+    pub const unsafe fn from_raw(raw: RawEntityHandle) -> Entity {
         Self { handle: raw }
     }
 
@@ -36,48 +36,52 @@ impl Entity {
         Self::INVALID
     }
 
-    /// Manually construct a new Entity. index MUST be lower than 2^24!
+    /// Manually construct a new Entity. index MUST be lower than 2^ENTITY_HANDLE_BITS!
     /// Failing to uphold this invariant will corrupt the internal handle.
     /// # Safety
-    /// - index MUST be lower than 2^24!
-    pub const unsafe fn new_unchecked(index: u32, version: u8) -> Entity {
+    /// - index MUST be lower than 2^ENTITY_HANDLE_BITS!
+    pub const unsafe fn new_unchecked(index: u32, version: EntityVersion) -> Entity {
         Entity {
-            handle: (index << ENTITY_VERSION_BITS) | version as u32,
+            handle: ((index as RawEntityHandle) << ENTITY_VERSION_BITS)
+                | version as RawEntityHandle,
         }
     }
 
     /// Returns the index part of the entity's handle.
+    #[allow(clippy::unnecessary_cast)] // The handle is a u64 with wide entity handles.
     pub const fn index(&self) -> u32 {
-        self.handle >> ENTITY_VERSION_BITS
+        (self.handle >> ENTITY_VERSION_BITS) as u32
     }
 
     /// Sets the index part of the entity's handle.
-    /// index MUST be lower than 2^24!
+    /// index MUST be lower than 2^ENTITY_HANDLE_BITS!
     /// Failing to uphold this invariant will corrupt the internal handle.
     /// # Safety
-    /// - index MUST be lower than 2^24!
+    /// - index MUST be lower than 2^ENTITY_HANDLE_BITS!
     pub unsafe fn set_index(&mut self, index: u32) {
         debug_assert!(
-            index < 2u32.pow(ENTITY_HANDLE_BITS as u32),
-            "Entity index must be < 2^24!"
+            index as u64 <= MAX_ENTITY_HANDLE_VALUE as u64,
+            "Entity index must be < 2^ENTITY_HANDLE_BITS!"
         );
 
-        let version: u8 = (0xFFFFFF & self.handle) as u8;
-        self.handle = (index << ENTITY_VERSION_BITS) | version as u32;
+        let version = self.version();
+        self.handle =
+            ((index as RawEntityHandle) << ENTITY_VERSION_BITS) | version as RawEntityHandle;
     }
 
     /// Returns the version part of the entity's handle.
-    pub const fn version(&self) -> u8 {
-        (0xFFFFFF & self.handle) as u8
+    pub const fn version(&self) -> EntityVersion {
+        self.handle as EntityVersion
     }
 
     /// Sets the version part of the entity's handle.
-    pub fn set_version(&mut self, version: u8) {
-        self.handle = (self.index() << ENTITY_VERSION_BITS) | version as u32;
+    pub fn set_version(&mut self, version: EntityVersion) {
+        self.handle =
+            ((self.index() as RawEntityHandle) << ENTITY_VERSION_BITS) | version as RawEntityHandle;
     }
 
     /// Returns the raw entity handle.
-    pub const fn raw(&self) -> u32 {
+    pub const fn raw(&self) -> RawEntityHandle {
         self.handle
     }
 }
@@ -98,5 +102,18 @@ fn test_entity_handles() {
     assert_eq!(entity.index(), 30);
     assert_eq!(entity.version(), 20);
 
-    assert_eq!(Entity::invalid().raw(), u32::MAX);
+    assert_eq!(Entity::invalid().raw(), RawEntityHandle::MAX);
+}
+
+#[cfg(feature = "wide_entity_handles")]
+#[test]
+fn test_wide_entity_handles() {
+    let mut entity = unsafe { Entity::new_unchecked(4_000_000_000, 70_000) };
+    assert_eq!(entity.index(), 4_000_000_000);
+    assert_eq!(entity.version(), 70_000);
+
+    entity.set_version(EntityVersion::MAX);
+    assert_eq!(entity.version(), EntityVersion::MAX);
+    assert_eq!(entity.index(), 4_000_000_000);
+    assert!(entity.is_valid());
 }
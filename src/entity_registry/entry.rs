@@ -1,62 +1,83 @@
 use super::*;
-use crate::INVALID_ARCHETYPE_INDEX;
+use crate::{EntityVersion, INVALID_ARCHETYPE_INDEX, RESERVED_ARCHETYPE_INDEX};
 
 /// Represents entity reference to the [ version | index_in_archetype | archetype_index | length | custom_bits ].
 /// MEMORY_LAYOUTS:
 /// Valid:      |version: u8|idx_in_arch: u24|arch_idx: u16 ]
 /// Invalid:    |version: u8|next_fr_slt: u24|INV_ARCH: u16 ]
 ///             |     0     | 1    2     3   | 4       5    ]
+/// With the `wide_entity_handles` feature, version and index_in_archetype both take up 4 bytes:
+/// Valid:      |version: u32|idx_in_arch: u32|arch_idx: u16 ]
+/// Invalid:    |version: u32|next_fr_slt: u32|INV_ARCH: u16 ]
+///             | 0  1  2  3 | 4  5   6   7   | 8       9    ]
 #[repr(C)]
 #[derive(Clone, Debug)]
 pub struct EntityEntry {
-    values: [u8; 6],
+    values: [u8; Self::SIZE],
 }
 
 impl Default for EntityEntry {
     fn default() -> Self {
         Self {
-            values: [
-                0, // version               byte 1
-                0, // index_in_archetype    byte 1
-                0, // index_in_archetype    byte 2
-                0, // index_in_archetype    byte 3
-                0, // archetype_index       byte 1
-                0, // archetype_index       byte 2
-            ],
+            values: [0; Self::SIZE],
         }
     }
 }
 
 impl EntityEntry {
-    pub const fn version(&self) -> u8 {
-        return self.values[0];
+    const VERSION_OFFSET: usize = 0;
+    const VERSION_BYTES: usize = core::mem::size_of::<EntityVersion>();
+    const INDEX_IN_ARCHETYPE_OFFSET: usize = Self::VERSION_OFFSET + Self::VERSION_BYTES;
+    const ARCHETYPE_INDEX_OFFSET: usize = Self::INDEX_IN_ARCHETYPE_OFFSET + IndexInArchetype::BYTES;
+    const SIZE: usize = Self::ARCHETYPE_INDEX_OFFSET + core::mem::size_of::<u16>();
+
+    pub const fn version(&self) -> EntityVersion {
+        let mut bytes = [0; Self::VERSION_BYTES];
+        let mut i = 0;
+        while i < Self::VERSION_BYTES {
+            bytes[i] = self.values[Self::VERSION_OFFSET + i];
+            i += 1;
+        }
+        EntityVersion::from_ne_bytes(bytes)
     }
-    pub fn set_version(&mut self, version: u8) {
-        self.values[0] = version;
+    pub fn set_version(&mut self, version: EntityVersion) {
+        self.values[Self::VERSION_OFFSET..Self::INDEX_IN_ARCHETYPE_OFFSET]
+            .copy_from_slice(&version.to_ne_bytes());
     }
     pub const fn index_in_archetype(&self) -> IndexInArchetype {
-        IndexInArchetype::from_bytes([self.values[1], self.values[2], self.values[3]])
+        let mut bytes = [0; IndexInArchetype::BYTES];
+        let mut i = 0;
+        while i < IndexInArchetype::BYTES {
+            bytes[i] = self.values[Self::INDEX_IN_ARCHETYPE_OFFSET + i];
+            i += 1;
+        }
+        IndexInArchetype::from_bytes(bytes)
     }
     pub fn set_index_in_archetype(&mut self, index_in_archetype: IndexInArchetype) {
-        self.values[1] = index_in_archetype.to_bytes()[0];
-        self.values[2] = index_in_archetype.to_bytes()[1];
-        self.values[3] = index_in_archetype.to_bytes()[2];
+        self.values[Self::INDEX_IN_ARCHETYPE_OFFSET..Self::ARCHETYPE_INDEX_OFFSET]
+            .copy_from_slice(&index_in_archetype.to_bytes());
     }
     pub const fn archetype_index(&self) -> u16 {
-        u16::from_ne_bytes([self.values[4], self.values[5]])
+        u16::from_ne_bytes([
+            self.values[Self::ARCHETYPE_INDEX_OFFSET],
+            self.values[Self::ARCHETYPE_INDEX_OFFSET + 1],
+        ])
     }
     pub fn set_archetype_index(&mut self, archetype_index: u16) {
-        let bytes = archetype_index.to_ne_bytes();
-        self.values[4] = bytes[0];
-        self.values[5] = bytes[1];
+        self.values[Self::ARCHETYPE_INDEX_OFFSET..Self::SIZE]
+            .copy_from_slice(&archetype_index.to_ne_bytes());
     }
     /// Checks if this entry points to a valid entity.
     pub const fn is_valid(&self) -> bool {
-        return self.archetype_index() != INVALID_ARCHETYPE_INDEX;
+        return self.archetype_index() != INVALID_ARCHETYPE_INDEX && !self.is_reserved();
+    }
+    /// Checks if this entry belongs to a reserved entity which has not been created yet.
+    pub const fn is_reserved(&self) -> bool {
+        self.archetype_index() == RESERVED_ARCHETYPE_INDEX
     }
     /// Sets the archetype index to invalid, indicating this entry does not point to a existing entity.
     /// # Safety
-    /// - next_free_slot must at most be 24 bits.
+    /// - next_free_slot must at most be 24 bits, or 32 bits with the `wide_entity_handles` feature.
     pub unsafe fn invalidate(&mut self, next_free_slot: u32) {
         self.set_index_in_archetype(IndexInArchetype::new_unchecked(next_free_slot));
         self.set_archetype_index(INVALID_ARCHETYPE_INDEX);
@@ -65,9 +86,21 @@ impl EntityEntry {
 
 #[cfg(test)]
 mod tests {
-    use super::{EntityEntry, IndexInArchetype};
+    use super::{EntityEntry, EntityVersion, IndexInArchetype};
+    use core::ops::Range;
 
-    const TEST_VALUES_U8: [u8; 10] = [0, 3, 7, 12, 89, u8::MAX - 1, u8::MAX, 72, 134, 1];
+    const TEST_VALUES_VERSION: [EntityVersion; 10] = [
+        0,
+        3,
+        7,
+        12,
+        89,
+        EntityVersion::MAX - 1,
+        EntityVersion::MAX,
+        72,
+        134,
+        1,
+    ];
     const TEST_VALUES_U16: [u16; 10] = [0, 1, 3, 7, 234, 29304, 13032, u16::MAX, u16::MAX - 1, 1];
     const TEST_VALUES_U32: [u32; 10] = [
         0,
@@ -85,9 +118,9 @@ mod tests {
     /// Checks if bytes that are not in the mutation_allowed list are modified.
     /// Returns true if they're not mutated.
     fn check_bytes(
-        before_bytes: &[u8; 6],
-        after_bytes: &[u8; 6],
-        mutation_allowed: &[usize],
+        before_bytes: &[u8; EntityEntry::SIZE],
+        after_bytes: &[u8; EntityEntry::SIZE],
+        mutation_allowed: Range<usize>,
     ) -> bool {
         for i in 0..before_bytes.len() {
             if mutation_allowed.contains(&i) {
@@ -115,25 +148,37 @@ mod tests {
         }
 
         // Test version field
-        for value in TEST_VALUES_U8 {
+        for value in TEST_VALUES_VERSION {
             let before_bytes = entry.values;
             entry.set_version(value);
             assert_eq!(entry.version(), value);
-            assert!(check_bytes(&before_bytes, &entry.values, &[0]));
+            assert!(check_bytes(
+                &before_bytes,
+                &entry.values,
+                EntityEntry::VERSION_OFFSET..EntityEntry::INDEX_IN_ARCHETYPE_OFFSET
+            ));
         }
         // Test archetype index field.
         for value in TEST_VALUES_U16 {
             let before_bytes = entry.values;
             entry.set_archetype_index(value);
             assert_eq!(entry.archetype_index(), value);
-            assert!(check_bytes(&before_bytes, &entry.values, &[4, 5]));
+            assert!(check_bytes(
+                &before_bytes,
+                &entry.values,
+                EntityEntry::ARCHETYPE_INDEX_OFFSET..EntityEntry::SIZE
+            ));
         }
         // Test index in archetype field.
         for value in TEST_VALUES_U32 {
             let before_bytes = entry.values;
             entry.set_index_in_archetype(unsafe { IndexInArchetype::new_unchecked(value) });
             assert_eq!(entry.index_in_archetype().value(), value);
-            assert!(check_bytes(&before_bytes, &entry.values, &[1, 2, 3]));
+            assert!(check_bytes(
+                &before_bytes,
+                &entry.values,
+                EntityEntry::INDEX_IN_ARCHETYPE_OFFSET..EntityEntry::ARCHETYPE_INDEX_OFFSET
+            ));
         }
     }
 }
@@ -5,11 +5,14 @@ pub struct IndexInArchetype {
 }
 
 impl IndexInArchetype {
-    // Equivalent to 2^24 - 1, as the stored value is [0 - 2^24) excluding 2^24 itself.
-    pub const INVALID_VALUE: u32 = 16777215;
+    // Equivalent to 2^ENTITY_HANDLE_BITS - 1, as the stored value is [0 - 2^ENTITY_HANDLE_BITS) excluding the maximum itself.
+    pub const INVALID_VALUE: u32 = crate::MAX_ENTITY_HANDLE_VALUE;
+    /// The amount of bytes used by the byte representation of the value.
+    pub const BYTES: usize = crate::ENTITY_HANDLE_BITS as usize / 8;
 
     /// Constructs a new IndexInArchetype using `value`.
     /// Returns `None` in case `value` >= Self::INVALID_VALUE.
+    #[allow(clippy::absurd_extreme_comparisons)] // INVALID_VALUE is u32::MAX with wide entity handles.
     pub const fn new(value: u32) -> Option<IndexInArchetype> {
         if value >= Self::INVALID_VALUE {
             return None;
@@ -23,22 +26,26 @@ impl IndexInArchetype {
     }
 
     /// Constructs a new IndexInArchetype from raw bytes.
-    pub const fn from_bytes(bytes: [u8; 3]) -> IndexInArchetype {
+    pub const fn from_bytes(bytes: [u8; Self::BYTES]) -> IndexInArchetype {
         let mut value = 0;
-        value += (bytes[0] as u32) << 16;
-        value += (bytes[1] as u32) << 8;
-        value += bytes[2] as u32;
+        let mut i = 0;
+        while i < Self::BYTES {
+            value += (bytes[i] as u32) << (8 * (Self::BYTES - 1 - i));
+            i += 1;
+        }
         Self { value }
     }
 
-    /// Returns the triple byte representation of the value.
-    /// This is a shifted representation storing only the 3 most significant bytes.
-    pub const fn to_bytes(&self) -> [u8; 3] {
-        [
-            ((self.value & 0x00FF0000) >> 16) as u8,
-            ((self.value & 0x0000FF00) >> 8) as u8,
-            (self.value & 0x000000FF) as u8,
-        ]
+    /// Returns the [`Self::BYTES`] byte representation of the value.
+    /// This is a shifted representation storing only the [`Self::BYTES`] least significant bytes, most significant first.
+    pub const fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        let mut i = 0;
+        while i < Self::BYTES {
+            bytes[i] = (self.value >> (8 * (Self::BYTES - 1 - i))) as u8;
+            i += 1;
+        }
+        bytes
     }
 
     /// Returns the raw numeric value of the index in archetype.
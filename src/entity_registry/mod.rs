@@ -3,18 +3,28 @@ mod archetype_index;
 mod entry;
 mod iterator;
 mod index_in_archetype;
+mod reserver;
 
 pub use entity::*;
 pub use entry::*;
 pub use iterator::*;
 pub use index_in_archetype::*;
 pub use archetype_index::*;
+pub(crate) use reserver::EntityReserver;
 
+use alloc::collections::TryReserveError;
 use alloc::vec::*;
+use core::sync::atomic::{AtomicU32, Ordering};
+use crate::{MAX_ENTITY_VERSION_VALUE, RESERVED_ARCHETYPE_INDEX};
 
 pub struct EntityRegistry {
     entities: Vec<EntityEntry>,
     next_free_slot: u32,
+    // Head of the free list after reservations through shared references.
+    // Slots between next_free_slot and this one have been reserved, see settle_reserved.
+    reserved_free_slot: AtomicU32,
+    reserver: EntityReserver,
+    retired_slot_count: u32,
 }
 
 impl Default for EntityRegistry {
@@ -22,6 +32,9 @@ impl Default for EntityRegistry {
         Self {
             entities: Vec::new(),
             next_free_slot: IndexInArchetype::INVALID_VALUE,
+            reserved_free_slot: AtomicU32::new(IndexInArchetype::INVALID_VALUE),
+            reserver: EntityReserver::default(),
+            retired_slot_count: 0,
         }
     }
 }
@@ -37,20 +50,22 @@ impl EntityRegistry {
         if self.entities.len() >= Self::MAX_ENTITY_COUNT {
             return None;
         }
+        self.settle_reserved();
         let (entity, entry) = if self.next_free_slot == IndexInArchetype::INVALID_VALUE {
             // Linked list of free slots is empty, so we need to allocate a new entity.
-            self.entities.push(EntityEntry::default());
-            let idx = self.entities.len() - 1;
+            let idx = self.reserver.reserve_index()?;
+            self.extend_reserved(idx);
             (
-                unsafe { Entity::new_unchecked(idx as u32, 0) },
-                self.entities.last_mut().unwrap(),
+                unsafe { Entity::new_unchecked(idx, 0) },
+                &mut self.entities[idx as usize],
             )
         } else {
             let slot = self.next_free_slot;
-            let entry = &mut self.entities[slot as usize];
-            self.next_free_slot = entry.index_in_archetype().value();
+            let entry = &self.entities[slot as usize];
+            let version = entry.version();
+            self.set_next_free_slot(entry.index_in_archetype().value());
             (
-                unsafe { Entity::new_unchecked(slot as u32, entry.version()) },
+                unsafe { Entity::new_unchecked(slot as u32, version) },
                 &mut self.entities[slot as usize]
             )
         };
@@ -59,20 +74,211 @@ impl EntityRegistry {
         Some(entity)
     }
 
+    /// Creates an entity like [`EntityRegistry::create_entity`].
+    /// Returns Err instead of panicking if the entries could not grow, Ok(None) if the maximum amount of entities is exceeded.
+    pub fn try_create_entity(&mut self, index_in_archetype: IndexInArchetype, archetype_index: ArchetypeIndex) -> Result<Option<Entity>, TryReserveError> {
+        self.settle_reserved();
+        if self.next_free_slot == IndexInArchetype::INVALID_VALUE {
+            // A new slot follows all reserved ones, so make room for their entries as well.
+            let required = self.reserver.reserved_len() as usize + 1;
+            self.entities.try_reserve(required.saturating_sub(self.entities.len()))?;
+        }
+        Ok(self.create_entity(index_in_archetype, archetype_index))
+    }
+
+    /// Creates up to [`count`] entities at once, stored consecutively in the given archetype
+    /// starting at [`first_index_in_archetype`].
+    /// Reuses free slots first, the remaining entities are allocated in a single step.
+    /// Returns fewer entities if the maximum amount of entities is exceeded.
+    pub fn create_entities(&mut self, count: u32, first_index_in_archetype: u32, archetype_index: ArchetypeIndex) -> Vec<Entity> {
+        self.settle_reserved();
+        let mut entities = Vec::with_capacity(count as usize);
+        while entities.len() < count as usize && self.next_free_slot != IndexInArchetype::INVALID_VALUE {
+            let slot = self.next_free_slot;
+            let entry = &self.entities[slot as usize];
+            let version = entry.version();
+            self.set_next_free_slot(entry.index_in_archetype().value());
+            entities.push(unsafe { Entity::new_unchecked(slot, version) });
+        }
+        let indices = self.reserver.reserve_indices(count - entities.len() as u32);
+        if !indices.is_empty() {
+            self.extend_reserved(indices.end - 1);
+        }
+        entities.extend(indices.map(|idx| unsafe { Entity::new_unchecked(idx, 0) }));
+        for (offset, entity) in entities.iter().enumerate() {
+            let entry = &mut self.entities[entity.index() as usize];
+            entry.set_archetype_index(archetype_index.value());
+            entry.set_index_in_archetype(unsafe {
+                IndexInArchetype::new_unchecked(first_index_in_archetype + offset as u32)
+            });
+        }
+        entities
+    }
+
+    /// Creates an entity using a handle previously reserved through the reserver of this registry.
+    /// Returns false if the handle was not reserved or was already created.
+    pub fn create_reserved_entity(&mut self, entity: Entity, index_in_archetype: IndexInArchetype, archetype_index: ArchetypeIndex) -> bool {
+        if entity == Entity::INVALID || entity.index() >= self.reserver.reserved_len() {
+            return false;
+        }
+        self.settle_reserved();
+        self.extend_reserved(entity.index());
+        let entry = &mut self.entities[entity.index() as usize];
+        if !entry.is_reserved() || entry.version() != entity.version() {
+            return false;
+        }
+        entry.set_archetype_index(archetype_index.value());
+        entry.set_index_in_archetype(index_in_archetype);
+        true
+    }
+
+    /// Releases a reserved handle which will not be created, making its slot available again.
+    /// Returns false if the handle is not reserved.
+    pub fn release_reserved_entity(&mut self, entity: Entity) -> bool {
+        if entity == Entity::INVALID || entity.index() >= self.reserver.reserved_len() {
+            return false;
+        }
+        self.settle_reserved();
+        self.extend_reserved(entity.index());
+        let entry = &mut self.entities[entity.index() as usize];
+        if !entry.is_reserved() || entry.version() != entity.version() {
+            return false;
+        }
+        self.free_slot(entity.index());
+        true
+    }
+
+    /// Reserves an entity handle without requiring mutable access, reusing a free slot if available.
+    /// The entity is created once it is passed to [`EntityRegistry::create_reserved_entity`].
+    /// Returns None if the maximum amount of entities is exceeded.
+    pub fn reserve_entity(&self) -> Option<Entity> {
+        let popped = self.reserved_free_slot.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |slot| {
+            // Entries are not modified while shared, so only concurrent reservations can move the head.
+            (slot != IndexInArchetype::INVALID_VALUE)
+                .then(|| self.entities[slot as usize].index_in_archetype().value())
+        });
+        match popped {
+            Ok(slot) => unsafe {
+                Some(Entity::new_unchecked(slot, self.entities[slot as usize].version()))
+            },
+            Err(_) => self.reserver.reserve_entity(),
+        }
+    }
+
+    /// Returns all reserved entities which have not been created or released yet.
+    pub fn reserved_entities(&mut self) -> Vec<Entity> {
+        self.settle_reserved();
+        if let Some(last) = self.reserver.reserved_len().checked_sub(1) {
+            self.extend_reserved(last);
+        }
+        self.entities
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.is_reserved())
+            .map(|(index, entry)| unsafe { Entity::new_unchecked(index as u32, entry.version()) })
+            .collect()
+    }
+
+    /// Returns the amount of slots which are retired because their version is exhausted.
+    pub fn retired_slot_count(&self) -> u32 {
+        self.retired_slot_count
+    }
+
+    pub(crate) fn reserver(&self) -> &EntityReserver {
+        &self.reserver
+    }
+
+    /// Marks the free slots reserved through [`EntityRegistry::reserve_entity`] as reserved,
+    /// and removes them from the list of free slots.
+    fn settle_reserved(&mut self) {
+        let head = *self.reserved_free_slot.get_mut();
+        while self.next_free_slot != head {
+            let entry = &mut self.entities[self.next_free_slot as usize];
+            self.next_free_slot = entry.index_in_archetype().value();
+            entry.set_archetype_index(RESERVED_ARCHETYPE_INDEX);
+        }
+    }
+
+    /// Invalidates the slot and pushes it onto the list of free slots.
+    /// Slots whose version is exhausted are retired instead, so stale handles can never alias a new entity.
+    fn free_slot(&mut self, slot: u32) {
+        let entry = &mut self.entities[slot as usize];
+        if entry.version() == MAX_ENTITY_VERSION_VALUE {
+            unsafe { entry.invalidate(IndexInArchetype::INVALID_VALUE) };
+            self.retired_slot_count += 1;
+            return;
+        }
+        unsafe { entry.invalidate(self.next_free_slot) };
+        entry.set_version(entry.version() + 1);
+        self.set_next_free_slot(slot);
+    }
+
+    fn set_next_free_slot(&mut self, slot: u32) {
+        self.next_free_slot = slot;
+        *self.reserved_free_slot.get_mut() = slot;
+    }
+
+    /// Makes sure an entry exists for the given reserved index.
+    /// Entries for reserved indices which have not been created yet are marked as reserved.
+    fn extend_reserved(&mut self, index: u32) {
+        if (index as usize) < self.entities.len() {
+            return;
+        }
+        let mut entry = EntityEntry::default();
+        entry.set_archetype_index(RESERVED_ARCHETYPE_INDEX);
+        self.entities.resize(index as usize + 1, entry);
+    }
+
     pub fn destroy_entity(&mut self, entity: Entity) -> bool {
         if entity.index() as usize >= self.entities.len() || entity == Entity::INVALID {
             return false;
         }
+        self.settle_reserved();
         let entry = &mut self.entities[entity.index() as usize];
         if entry.version() != entity.version() || !entry.is_valid() {
             return false;
         }
-        unsafe { entry.invalidate(self.next_free_slot) };
-        self.next_free_slot = entity.index();
-        entry.set_version(entry.version().wrapping_add(1));
+        self.free_slot(entity.index());
         true
     }
 
+    /// Reserves capacity for at least [`additional`] more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+    }
+
+    /// Reserves capacity for at least [`additional`] more entries.
+    /// Returns Err instead of panicking if the entries could not grow.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.entities.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the entries as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.entities.shrink_to_fit();
+    }
+
+    /// Destroys all given entities at once, invalid entities are skipped.
+    pub fn destroy_entities(&mut self, entities: &[Entity]) {
+        self.settle_reserved();
+        for entity in entities {
+            if self.entity_entry(*entity).is_some() {
+                self.free_slot(entity.index());
+            }
+        }
+    }
+
+    /// Destroys all entities, pushing their slots onto the list of free slots in index order.
+    /// Reserved entities which have not been created yet remain reserved.
+    pub fn clear(&mut self) {
+        self.settle_reserved();
+        for slot in (0..self.entities.len() as u32).rev() {
+            if self.entities[slot as usize].is_valid() {
+                self.free_slot(slot);
+            }
+        }
+    }
+
     pub fn entity_entry(&self, entity: Entity) -> Option<&EntityEntry> {
         if entity.index() as usize >= self.entities.len() || entity == Entity::INVALID {
             return None;
@@ -156,6 +362,96 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_entity_register_reserve() {
+        let mut register = EntityRegistry::default();
+        let index_in_archetype = IndexInArchetype::new(0).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+
+        let entities = (0..4)
+            .filter_map(|_| register.create_entity(index_in_archetype, archetype_index))
+            .collect::<Vec<_>>();
+        assert!(register.destroy_entity(entities[1]));
+        assert!(register.destroy_entity(entities[2]));
+
+        // Free slots are reserved first, after which the index space is extended.
+        let reserved = (0..3).map(|_| register.reserve_entity().unwrap()).collect::<Vec<_>>();
+        assert_eq!(reserved.iter().map(|e| e.index()).collect::<Vec<_>>(), [2, 1, 4]);
+        assert_eq!(reserved.iter().map(|e| e.version()).collect::<Vec<_>>(), [1, 1, 0]);
+        assert!(reserved.iter().all(|e| register.entity_entry(*e).is_none()));
+
+        // Reserved slots are never handed out by create_entity.
+        let created = register.create_entity(index_in_archetype, archetype_index).unwrap();
+        assert_eq!(created.index(), 5);
+        assert_eq!(register.reserved_entities(), [reserved[1], reserved[0], reserved[2]]);
+
+        assert!(register.create_reserved_entity(reserved[0], index_in_archetype, archetype_index));
+        assert!(!register.create_reserved_entity(reserved[0], index_in_archetype, archetype_index));
+        assert!(register.release_reserved_entity(reserved[2]));
+        assert!(!register.release_reserved_entity(reserved[2]));
+        assert!(register.entity_entry(reserved[0]).is_some());
+        assert!(register.entity_entry(reserved[2]).is_none());
+        assert_eq!(register.reserved_entities(), [reserved[1]]);
+        assert!(register.create_reserved_entity(reserved[1], index_in_archetype, archetype_index));
+        assert!(register.reserved_entities().is_empty());
+        verify_linked_list(&register);
+    }
+
+    #[test]
+    fn test_entity_register_create_entities() {
+        let mut register = EntityRegistry::default();
+        let index_in_archetype = IndexInArchetype::new(0).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+
+        let entities = (0..3)
+            .filter_map(|_| register.create_entity(index_in_archetype, archetype_index))
+            .collect::<Vec<_>>();
+        assert!(register.destroy_entity(entities[1]));
+
+        // Free slots are reused first, after which the index space is extended in one step.
+        let created = register.create_entities(3, 5, archetype_index);
+        assert_eq!(created.iter().map(|e| e.index()).collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(created[0].version(), 1);
+        for (offset, entity) in created.iter().enumerate() {
+            let entry = register.entity_entry(*entity).unwrap();
+            assert_eq!(entry.archetype_index(), archetype_index.value());
+            assert_eq!(entry.index_in_archetype().value(), 5 + offset as u32);
+        }
+        assert!(register.create_entities(0, 0, archetype_index).is_empty());
+        assert!(register.reserved_entities().is_empty());
+        verify_linked_list(&register);
+    }
+
+    #[test]
+    fn test_entity_register_retire_exhausted() {
+        let mut register = EntityRegistry::default();
+        let index_in_archetype = IndexInArchetype::new(0).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+
+        let entity = register.create_entity(index_in_archetype, archetype_index).unwrap();
+        register.entities[entity.index() as usize].set_version(MAX_ENTITY_VERSION_VALUE - 1);
+        let entity = unsafe { Entity::new_unchecked(entity.index(), MAX_ENTITY_VERSION_VALUE - 1) };
+
+        // The second to last version is recycled as usual.
+        assert!(register.destroy_entity(entity));
+        assert_eq!(register.retired_slot_count(), 0);
+        let last = register.create_entity(index_in_archetype, archetype_index).unwrap();
+        assert_eq!(last.index(), entity.index());
+        assert_eq!(last.version(), MAX_ENTITY_VERSION_VALUE);
+
+        // The last version is retired instead of wrapping around.
+        assert!(register.destroy_entity(last));
+        assert_eq!(register.retired_slot_count(), 1);
+        assert_eq!(register.next_free_slot, IndexInArchetype::INVALID_VALUE);
+        let next = register.create_entity(index_in_archetype, archetype_index).unwrap();
+        assert_ne!(next.index(), entity.index());
+        assert!(register.entity_entry(entity).is_none());
+        assert!(register.entity_entry(last).is_none());
+        assert!(!register.destroy_entity(last));
+        assert_eq!(register.retired_slot_count(), 1);
+        assert_eq!(register.iter().collect::<Vec<_>>(), [next]);
+    }
+
     const ENTITY_COUNT: u32 = 1024;
     #[test]
     fn test_many_entities() {
@@ -0,0 +1,53 @@
+use super::Entity;
+use crate::MAX_ENTITY_HANDLE_VALUE;
+use alloc::sync::Arc;
+use core::ops::Range;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Hands out indices into the index space of an entity registry without requiring access to it.
+/// Clones share the same index space, allowing reservations from other threads.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EntityReserver {
+    next_index: Arc<AtomicU32>,
+}
+
+impl EntityReserver {
+    /// Reserves a new index past all previously reserved indices.
+    /// Returns None if the maximum amount of entities is exceeded.
+    pub(crate) fn reserve_index(&self) -> Option<u32> {
+        self.next_index
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |index| {
+                (index < MAX_ENTITY_HANDLE_VALUE).then_some(index + 1)
+            })
+            .ok()
+    }
+
+    /// Reserves up to [`count`] new consecutive indices past all previously reserved indices.
+    /// Returns fewer indices if the maximum amount of entities is exceeded.
+    pub(crate) fn reserve_indices(&self, count: u32) -> Range<u32> {
+        let start = self
+            .next_index
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |index| {
+                Some(index + count.min(MAX_ENTITY_HANDLE_VALUE - index))
+            })
+            .unwrap();
+        start..start + count.min(MAX_ENTITY_HANDLE_VALUE - start)
+    }
+
+    /// Reserves a new entity handle past all previously reserved indices.
+    /// Returns None if the maximum amount of entities is exceeded.
+    pub(crate) fn reserve_entity(&self) -> Option<Entity> {
+        self.reserve_index()
+            .map(|index| unsafe { Entity::new_unchecked(index, 0) })
+    }
+
+    /// Returns the amount of indices reserved so far.
+    pub(crate) fn reserved_len(&self) -> u32 {
+        self.next_index.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if both reservers share the same index space.
+    pub(crate) fn shares_index_space(&self, other: &EntityReserver) -> bool {
+        Arc::ptr_eq(&self.next_index, &other.next_index)
+    }
+}
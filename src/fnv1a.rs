@@ -2,10 +2,41 @@ const FNV_OFFSET_BASIS_32: u32 = 0x811c9dc5;
 #[allow(dead_code)]
 const FNV_OFFSET_BASIS_64: u64 = 0xcbf29ce484222325;
 
+#[allow(dead_code)]
+const FNV_OFFSET_BASIS_128: u128 = 0x6c62272e07bb014262b821756295c58d;
+
 const FNV_PRIME_32: u32 = 0x01000193;
 #[allow(dead_code)]
 const FNV_PRIME_64: u64 = 0x00000100000001B3;
 
+#[allow(dead_code)]
+const FNV_PRIME_128: u128 = 0x0000000001000000000000000000013B;
+
+/// Computes 128-bits fnv1a hash of the given slice, or up-to limit if provided.
+/// If limit is zero or exceeds slice length, slice length is used instead.
+pub const fn fnv1a_hash_128(bytes: &[u8], limit: Option<usize>) -> u128 {
+    let mut hash = FNV_OFFSET_BASIS_128;
+
+    let mut i = 0;
+    let len = match limit {
+        Some(v) => {
+            if v <= bytes.len() && v > 0 {
+                v
+            } else {
+                bytes.len()
+            }
+        }
+        None => bytes.len(),
+    };
+
+    while i < len {
+        hash ^= bytes[i] as u128;
+        hash = hash.wrapping_mul(FNV_PRIME_128);
+        i += 1;
+    }
+    hash
+}
+
 /// Computes 64-bits fnv1a hash of the given slice, or up-to limit if provided.
 /// If limit is zero or exceeds slice length, slice length is used instead.
 pub const fn fnv1a_hash_64(bytes: &[u8], limit: Option<usize>) -> u64 {
@@ -67,6 +98,12 @@ pub const fn fnv1a_hash_16_xor(bytes: &[u8], limit: Option<usize>) -> u16 {
     upper ^ lower
 }
 
+/// Computes 128-bit fnv1a hash from a str.
+#[allow(dead_code)]
+pub const fn fnv1a_hash_str_128(input: &str) -> u128 {
+    fnv1a_hash_128(input.as_bytes(), None)
+}
+
 /// Computes 64-bit fnv1a hash from a str.
 #[allow(dead_code)]
 pub const fn fnv1a_hash_str_64(input: &str) -> u64 {
@@ -1,20 +1,28 @@
 #![no_std]
 
 extern crate alloc;
+// Allows the derive macros to refer to `::shard_ecs` from within this crate as well.
+extern crate self as shard_ecs;
 
+pub mod allocator;
 pub mod archetype;
 pub mod archetype_registry;
+pub mod command_buffer;
 pub mod constants;
 pub mod descriptors;
 pub mod entity_registry;
 pub mod fnv1a;
+pub mod query;
 pub mod registry;
 
+pub use allocator::{AllocError, ArchetypeAllocator, Global};
 pub use archetype::Archetype;
+pub use command_buffer::CommandBuffer;
 pub use constants::*;
 pub use descriptors::*;
 pub use entity_registry::*;
-pub use registry::Registry;
+pub use query::*;
+pub use registry::{Registry, RegistryError, RegistryErrorKind};
 
 #[cfg(test)]
 mod test_components;
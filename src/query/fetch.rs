@@ -0,0 +1,509 @@
+use crate::allocator::ArchetypeAllocator;
+use crate::archetype::{Archetype, TickColumn};
+use crate::copy_component_descriptor_from_to;
+use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+use crate::descriptors::component_descriptor::ComponentDescriptor;
+use crate::{Component, MAX_COMPONENTS_PER_ENTITY};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use private::SealedQueryTerm;
+
+/// Query term which fetches a shared reference to component [`C`].
+pub struct Read<C: Component>(PhantomData<fn() -> C>);
+
+/// Query term which fetches a mutable reference to component [`C`].
+pub struct Write<C: Component>(PhantomData<fn() -> C>);
+
+/// Mutable access to a component column fetched by [`Write<C>`] during chunk iteration.
+/// The components of the column are only marked as changed once it is mutably dereferenced.
+pub struct ColumnMut<'a, C: Component> {
+    slice: &'a mut [C],
+    ticks: TickColumn,
+    marked: bool,
+}
+
+// The column is accessed exclusively, just like the slice it wraps.
+unsafe impl<'a, C: Component> Send for ColumnMut<'a, C> {}
+
+impl<'a, C: Component> Deref for ColumnMut<'a, C> {
+    type Target = [C];
+
+    fn deref(&self) -> &Self::Target {
+        self.slice
+    }
+}
+
+impl<'a, C: Component> DerefMut for ColumnMut<'a, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if !self.marked {
+            unsafe { self.ticks.set_all_changed(self.slice.len()) };
+            self.marked = true;
+        }
+        self.slice
+    }
+}
+
+/// Represents a single term of a query.
+/// Implemented for [`Read<C>`], [`Write<C>`], [`Option<C>`] and [`Option<Write<C>>`].
+/// Optional terms do not restrict which archetypes match, they fetch [`None`] for archetypes lacking the component.
+pub trait QueryTerm: SealedQueryTerm + 'static {
+    type Item<'a>;
+    type Slice<'a>;
+
+    /// Descriptor of the component type fetched by the term.
+    const DESCRIPTOR: ComponentDescriptor;
+    /// Whether the component type may be absent from matching archetypes.
+    const OPTIONAL: bool;
+
+    /// Returns the item at the given index of the column.
+    /// # Safety
+    /// - Column must point to the component column of the term or be null for absent optional terms.
+    /// - Index must not be out of bounds.
+    unsafe fn item<'a>(column: *mut u8, index: usize) -> Self::Item<'a>;
+
+    /// Returns the column of the archetype as a slice.
+    /// Mutable terms mark the column as changed with [`change_tick`] once it is mutably accessed.
+    /// # Safety
+    /// - Column must point to the component column of the term in the archetype or be null for absent optional terms.
+    /// - Mutable slices may only be fetched if the archetype is exclusively borrowed for 'a.
+    unsafe fn slice<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        column: *mut u8,
+        change_tick: u32,
+    ) -> Self::Slice<'a>;
+
+    /// Marks the fetched component of the entity at the given index as changed if the term is mutable.
+    /// # Safety
+    /// - Index must not be out of bounds.
+    /// - The archetype must not be accessed concurrently.
+    unsafe fn mark_changed<A: ArchetypeAllocator>(
+        _archetype: &Archetype<A>,
+        _index: u32,
+        _change_tick: u32,
+    ) {
+    }
+}
+
+/// Marker trait for query terms which never hand out mutable references.
+pub trait ReadOnlyQueryTerm: QueryTerm {}
+
+impl<C: Component> QueryTerm for Read<C> {
+    type Item<'a> = &'a C;
+    type Slice<'a> = &'a [C];
+
+    const DESCRIPTOR: ComponentDescriptor = C::DESCRIPTOR;
+    const OPTIONAL: bool = false;
+
+    unsafe fn item<'a>(column: *mut u8, index: usize) -> Self::Item<'a> {
+        &*(column as *const C).add(index)
+    }
+
+    unsafe fn slice<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        column: *mut u8,
+        _change_tick: u32,
+    ) -> Self::Slice<'a> {
+        core::slice::from_raw_parts(column as *const C, archetype.len() as usize)
+    }
+}
+
+impl<C: Component> QueryTerm for Write<C> {
+    type Item<'a> = &'a mut C;
+    type Slice<'a> = ColumnMut<'a, C>;
+
+    const DESCRIPTOR: ComponentDescriptor = C::DESCRIPTOR;
+    const OPTIONAL: bool = false;
+
+    unsafe fn item<'a>(column: *mut u8, index: usize) -> Self::Item<'a> {
+        &mut *(column as *mut C).add(index)
+    }
+
+    unsafe fn slice<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        column: *mut u8,
+        change_tick: u32,
+    ) -> Self::Slice<'a> {
+        ColumnMut {
+            slice: core::slice::from_raw_parts_mut(column as *mut C, archetype.len() as usize),
+            ticks: archetype.tick_column(C::ID, change_tick),
+            marked: false,
+        }
+    }
+
+    unsafe fn mark_changed<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        change_tick: u32,
+    ) {
+        archetype.mark_component_changed_unchecked(C::ID, index, change_tick);
+    }
+}
+
+impl<C: Component> QueryTerm for Option<C> {
+    type Item<'a> = Option<&'a C>;
+    type Slice<'a> = Option<&'a [C]>;
+
+    const DESCRIPTOR: ComponentDescriptor = C::DESCRIPTOR;
+    const OPTIONAL: bool = true;
+
+    unsafe fn item<'a>(column: *mut u8, index: usize) -> Self::Item<'a> {
+        match column.is_null() {
+            true => None,
+            false => Some(Read::<C>::item(column, index)),
+        }
+    }
+
+    unsafe fn slice<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        column: *mut u8,
+        change_tick: u32,
+    ) -> Self::Slice<'a> {
+        match column.is_null() {
+            true => None,
+            false => Some(Read::<C>::slice(archetype, column, change_tick)),
+        }
+    }
+}
+
+impl<C: Component> QueryTerm for Option<Write<C>> {
+    type Item<'a> = Option<&'a mut C>;
+    type Slice<'a> = Option<ColumnMut<'a, C>>;
+
+    const DESCRIPTOR: ComponentDescriptor = C::DESCRIPTOR;
+    const OPTIONAL: bool = true;
+
+    unsafe fn item<'a>(column: *mut u8, index: usize) -> Self::Item<'a> {
+        match column.is_null() {
+            true => None,
+            false => Some(Write::<C>::item(column, index)),
+        }
+    }
+
+    unsafe fn slice<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        column: *mut u8,
+        change_tick: u32,
+    ) -> Self::Slice<'a> {
+        match column.is_null() {
+            true => None,
+            false => Some(Write::<C>::slice(archetype, column, change_tick)),
+        }
+    }
+
+    unsafe fn mark_changed<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        change_tick: u32,
+    ) {
+        archetype.mark_component_changed_unchecked(C::ID, index, change_tick);
+    }
+}
+
+impl<C: Component> ReadOnlyQueryTerm for Read<C> {}
+impl<C: Component> ReadOnlyQueryTerm for Option<C> {}
+
+/// Represents the set of terms fetched by a query. Implemented for single terms and tuples of terms.
+pub trait QueryFetch: 'static {
+    type Item<'a>;
+    type Slice<'a>;
+
+    /// Descriptor of all non optional terms, used to match archetypes.
+    /// Invalid if a component type occurs more than once or if all terms are optional.
+    const DESCRIPTOR: ArchetypeDescriptor;
+
+    /// Returns the column pointers of the archetype in term order.
+    /// Absent optional terms are represented by null pointers.
+    fn columns<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+    ) -> [*mut u8; MAX_COMPONENTS_PER_ENTITY];
+
+    /// Returns the items at the given index of the columns.
+    /// # Safety
+    /// - Columns must be obtained through [`QueryFetch::columns`] on a matching archetype.
+    /// - Index must not be out of bounds.
+    unsafe fn items<'a>(
+        columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+        index: usize,
+    ) -> Self::Item<'a>;
+
+    /// Returns the columns of the archetype as slices.
+    /// Mutable terms mark their columns as changed with [`change_tick`] once they are mutably accessed.
+    /// # Safety
+    /// - Columns must be obtained through [`QueryFetch::columns`] on the archetype, which must match.
+    /// - Mutable slices may only be fetched if the archetype is exclusively borrowed for 'a.
+    unsafe fn slices<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+        change_tick: u32,
+    ) -> Self::Slice<'a>;
+
+    /// Marks the components fetched by mutable terms of the entity at the given index as changed.
+    /// # Safety
+    /// - Index must not be out of bounds.
+    /// - The archetype must not be accessed concurrently.
+    unsafe fn mark_changed<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        change_tick: u32,
+    );
+}
+
+/// Marker trait for query fetches which never hand out mutable references.
+pub trait ReadOnlyQueryFetch: QueryFetch {}
+
+impl<T: QueryTerm> QueryFetch for T {
+    type Item<'a> = T::Item<'a>;
+    type Slice<'a> = T::Slice<'a>;
+
+    const DESCRIPTOR: ArchetypeDescriptor =
+        compute_fetch_descriptor(&[T::DESCRIPTOR], &[T::OPTIONAL]);
+
+    fn columns<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+    ) -> [*mut u8; MAX_COMPONENTS_PER_ENTITY] {
+        let mut columns = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+        columns[0] = archetype.column_pointer(&T::DESCRIPTOR);
+        columns
+    }
+
+    unsafe fn items<'a>(
+        columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+        index: usize,
+    ) -> Self::Item<'a> {
+        T::item(columns[0], index)
+    }
+
+    unsafe fn slices<'a, A: ArchetypeAllocator>(
+        archetype: &'a Archetype<A>,
+        columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+        change_tick: u32,
+    ) -> Self::Slice<'a> {
+        T::slice(archetype, columns[0], change_tick)
+    }
+
+    unsafe fn mark_changed<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        change_tick: u32,
+    ) {
+        T::mark_changed(archetype, index, change_tick);
+    }
+}
+
+impl<T: ReadOnlyQueryTerm> ReadOnlyQueryFetch for T {}
+
+macro_rules! impl_query_fetch_tuple {
+    ($(($elem:ident, $elem_idx:tt)), *) => {
+        impl<$($elem: QueryTerm),*> QueryFetch for ($($elem), *) {
+            type Item<'a> = ($(<$elem as QueryTerm>::Item<'a>), *);
+            type Slice<'a> = ($(<$elem as QueryTerm>::Slice<'a>), *);
+
+            const DESCRIPTOR: ArchetypeDescriptor = compute_fetch_descriptor(
+                &[$(<$elem as QueryTerm>::DESCRIPTOR), *],
+                &[$(<$elem as QueryTerm>::OPTIONAL), *],
+            );
+
+            fn columns<A: ArchetypeAllocator>(archetype: &Archetype<A>) -> [*mut u8; MAX_COMPONENTS_PER_ENTITY] {
+                let mut columns = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+                $(
+                    columns[$elem_idx] = archetype
+                        .column_pointer(&<$elem as QueryTerm>::DESCRIPTOR);
+                )*
+                columns
+            }
+
+            unsafe fn items<'a>(
+                columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+                index: usize,
+            ) -> Self::Item<'a> {
+                ($($elem::item(columns[$elem_idx], index)), *)
+            }
+
+            unsafe fn slices<'a, A: ArchetypeAllocator>(
+                archetype: &'a Archetype<A>,
+                columns: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+                change_tick: u32,
+            ) -> Self::Slice<'a> {
+                ($($elem::slice(archetype, columns[$elem_idx], change_tick)), *)
+            }
+
+            unsafe fn mark_changed<A: ArchetypeAllocator>(archetype: &Archetype<A>, index: u32, change_tick: u32) {
+                $($elem::mark_changed(archetype, index, change_tick);)*
+            }
+        }
+
+        impl<$($elem: ReadOnlyQueryTerm),*> ReadOnlyQueryFetch for ($($elem), *) {}
+    }
+}
+
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10),
+    (T12, 11),
+    (T13, 12),
+    (T14, 13)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10),
+    (T12, 11),
+    (T13, 12)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10),
+    (T12, 11)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7)
+);
+impl_query_fetch_tuple!(
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6)
+);
+impl_query_fetch_tuple!((T1, 0), (T2, 1), (T3, 2), (T4, 3), (T5, 4), (T6, 5));
+impl_query_fetch_tuple!((T1, 0), (T2, 1), (T3, 2), (T4, 3), (T5, 4));
+impl_query_fetch_tuple!((T1, 0), (T2, 1), (T3, 2), (T4, 3));
+impl_query_fetch_tuple!((T1, 0), (T2, 1), (T3, 2));
+impl_query_fetch_tuple!((T1, 0), (T2, 1));
+
+/// Computes the sorted archetype descriptor of all non optional terms.
+/// Returns [`ArchetypeDescriptor::INVALID`] if a component type occurs more than once.
+const fn compute_fetch_descriptor<const N: usize>(
+    descriptors: &[ComponentDescriptor; N],
+    optional: &[bool; N],
+) -> ArchetypeDescriptor {
+    // Duplicates MUST not exist, optional or not.
+    let mut i = 0;
+    while i < N {
+        let mut j = i + 1;
+        while j < N {
+            if descriptors[i].component_type_id.into_raw()
+                == descriptors[j].component_type_id.into_raw()
+            {
+                return ArchetypeDescriptor::INVALID;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+
+    let mut components = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
+    let mut len = 0;
+    i = 0;
+    while i < N {
+        if !optional[i] {
+            copy_component_descriptor_from_to!(descriptors[i], components[len]);
+            len += 1;
+        }
+        i += 1;
+    }
+
+    i = 0;
+    while i < len {
+        let mut j = i + 1;
+        while j < len {
+            if components[j].component_type_id.into_raw()
+                < components[i].component_type_id.into_raw()
+            {
+                let mut temp = ComponentDescriptor::INVALID;
+                copy_component_descriptor_from_to!(components[i], temp);
+                copy_component_descriptor_from_to!(components[j], components[i]);
+                copy_component_descriptor_from_to!(temp, components[j]);
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+
+    let id = ArchetypeDescriptor::compute_archetype_id(components.split_at(len).0);
+    ArchetypeDescriptor::new(id, len as u8, components)
+}
+
+mod private {
+    use super::{Read, Write};
+    use crate::Component;
+
+    pub trait SealedQueryTerm {}
+
+    impl<C: Component> SealedQueryTerm for Read<C> {}
+    impl<C: Component> SealedQueryTerm for Write<C> {}
+    impl<C: Component> SealedQueryTerm for Option<C> {}
+    impl<C: Component> SealedQueryTerm for Option<Write<C>> {}
+}
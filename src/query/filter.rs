@@ -0,0 +1,125 @@
+use crate::allocator::ArchetypeAllocator;
+use crate::archetype::Archetype;
+use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+use crate::Component;
+use core::marker::PhantomData;
+
+/// Query filter which only matches archetypes containing component [`C`].
+pub struct With<C: Component>(PhantomData<fn() -> C>);
+
+/// Query filter which only matches archetypes not containing component [`C`].
+pub struct Without<C: Component>(PhantomData<fn() -> C>);
+
+/// Query filter which only matches entities whose component [`C`] was changed since the last run of the query.
+#[cfg(feature = "change_detection")]
+pub struct Changed<C: Component>(PhantomData<fn() -> C>);
+
+/// Query filter which only matches entities whose component [`C`] was added since the last run of the query.
+#[cfg(feature = "change_detection")]
+pub struct Added<C: Component>(PhantomData<fn() -> C>);
+
+/// Represents the archetype filter of a query. Implemented for [`With<C>`], [`Without<C>`], `()` and tuples of filters.
+/// A tuple of filters only matches archetypes matching all of its filters.
+pub trait QueryFilter: 'static {
+    /// Returns whether the archetype described by the given descriptor passes the filter.
+    fn matches(descriptor: &ArchetypeDescriptor) -> bool;
+
+    /// Returns whether the entity at the given index of the archetype passes the filter.
+    /// Only called for archetypes passing [`QueryFilter::matches`].
+    /// [`last_run`] and [`this_run`] are the change ticks of the previous and the current run of the query.
+    fn matches_entity<A: ArchetypeAllocator>(
+        _archetype: &Archetype<A>,
+        _index: u32,
+        _last_run: u32,
+        _this_run: u32,
+    ) -> bool {
+        true
+    }
+}
+
+/// Marker trait for query filters which only depend on the archetype, and not on individual entities.
+/// Only these filters can be used for chunk iteration, see [`crate::Query::iter_chunks`].
+pub trait ArchetypeFilter: QueryFilter {}
+
+impl ArchetypeFilter for () {}
+impl<C: Component> ArchetypeFilter for With<C> {}
+impl<C: Component> ArchetypeFilter for Without<C> {}
+
+impl QueryFilter for () {
+    fn matches(_descriptor: &ArchetypeDescriptor) -> bool {
+        true
+    }
+}
+
+impl<C: Component> QueryFilter for With<C> {
+    fn matches(descriptor: &ArchetypeDescriptor) -> bool {
+        descriptor.has_component::<C>()
+    }
+}
+
+impl<C: Component> QueryFilter for Without<C> {
+    fn matches(descriptor: &ArchetypeDescriptor) -> bool {
+        !descriptor.has_component::<C>()
+    }
+}
+
+#[cfg(feature = "change_detection")]
+impl<C: Component> QueryFilter for Changed<C> {
+    fn matches(descriptor: &ArchetypeDescriptor) -> bool {
+        descriptor.has_component::<C>()
+    }
+
+    fn matches_entity<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        last_run: u32,
+        this_run: u32,
+    ) -> bool {
+        archetype
+            .component_ticks(C::ID, index)
+            .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+    }
+}
+
+#[cfg(feature = "change_detection")]
+impl<C: Component> QueryFilter for Added<C> {
+    fn matches(descriptor: &ArchetypeDescriptor) -> bool {
+        descriptor.has_component::<C>()
+    }
+
+    fn matches_entity<A: ArchetypeAllocator>(
+        archetype: &Archetype<A>,
+        index: u32,
+        last_run: u32,
+        this_run: u32,
+    ) -> bool {
+        archetype
+            .component_ticks(C::ID, index)
+            .is_some_and(|ticks| ticks.is_added(last_run, this_run))
+    }
+}
+
+macro_rules! impl_query_filter_tuple {
+    ($($elem:ident), *) => {
+        impl<$($elem: QueryFilter),*> QueryFilter for ($($elem,)*) {
+            fn matches(descriptor: &ArchetypeDescriptor) -> bool {
+                $($elem::matches(descriptor)) && *
+            }
+
+            fn matches_entity<A: ArchetypeAllocator>(archetype: &Archetype<A>, index: u32, last_run: u32, this_run: u32) -> bool {
+                $($elem::matches_entity(archetype, index, last_run, this_run)) && *
+            }
+        }
+
+        impl<$($elem: ArchetypeFilter),*> ArchetypeFilter for ($($elem,)*) {}
+    }
+}
+
+impl_query_filter_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_query_filter_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_query_filter_tuple!(T1, T2, T3, T4, T5, T6);
+impl_query_filter_tuple!(T1, T2, T3, T4, T5);
+impl_query_filter_tuple!(T1, T2, T3, T4);
+impl_query_filter_tuple!(T1, T2, T3);
+impl_query_filter_tuple!(T1, T2);
+impl_query_filter_tuple!(T1);
@@ -0,0 +1,148 @@
+use super::fetch::QueryFetch;
+use super::filter::QueryFilter;
+use crate::allocator::ArchetypeAllocator;
+use crate::archetype::Archetype;
+use crate::{Entity, MAX_COMPONENTS_PER_ENTITY};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+/// Iterates over all entities in the archetypes yielded by the inner iterator,
+/// fetching the query items of each entity passing the entity level filter.
+pub(crate) struct QueryIter<
+    'a,
+    A: ArchetypeAllocator + 'a,
+    F: QueryFetch,
+    R: QueryFilter,
+    I: Iterator<Item = &'a Archetype<A>>,
+> {
+    archetypes: I,
+    archetype: Option<&'a Archetype<A>>,
+    columns: [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+    entities: &'a [Entity],
+    index: usize,
+    last_run: u32,
+    this_run: u32,
+    _phantom: PhantomData<fn() -> (F, R)>,
+}
+
+impl<
+        'a,
+        A: ArchetypeAllocator + 'a,
+        F: QueryFetch,
+        R: QueryFilter,
+        I: Iterator<Item = &'a Archetype<A>>,
+    > QueryIter<'a, A, F, R, I>
+{
+    /// Creates a new query iterator.
+    /// Entities are filtered by the changes since [`last_run`], fetched mutable components are marked with [`this_run`].
+    /// # Safety
+    /// - All archetypes yielded by the inner iterator must match [`QueryFetch::DESCRIPTOR`].
+    /// - Mutable items may only be fetched if the archetypes are exclusively borrowed for 'a.
+    pub(crate) unsafe fn new(archetypes: I, last_run: u32, this_run: u32) -> Self {
+        Self {
+            archetypes,
+            archetype: None,
+            columns: [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY],
+            entities: &[],
+            index: 0,
+            last_run,
+            this_run,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        'a,
+        A: ArchetypeAllocator + 'a,
+        F: QueryFetch,
+        R: QueryFilter,
+        I: Iterator<Item = &'a Archetype<A>>,
+    > Iterator for QueryIter<'a, A, F, R, I>
+{
+    type Item = (Entity, F::Item<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.index >= self.entities.len() {
+                let archetype = self.archetypes.next()?;
+                self.archetype = Some(archetype);
+                self.columns = F::columns(archetype);
+                self.entities = archetype.entities();
+                self.index = 0;
+            }
+            let index = self.index;
+            self.index += 1;
+            let archetype = self.archetype?;
+            if !R::matches_entity(archetype, index as u32, self.last_run, self.this_run) {
+                continue;
+            }
+            unsafe {
+                F::mark_changed(archetype, index as u32, self.this_run);
+                return Some((self.entities[index], F::items(&self.columns, index)));
+            }
+        }
+    }
+}
+
+impl<
+        'a,
+        A: ArchetypeAllocator + 'a,
+        F: QueryFetch,
+        R: QueryFilter,
+        I: FusedIterator<Item = &'a Archetype<A>>,
+    > FusedIterator for QueryIter<'a, A, F, R, I>
+{
+}
+
+/// Iterates over the archetypes yielded by the inner iterator,
+/// fetching the entities and query slices of each archetype.
+pub(crate) struct QueryChunkIter<
+    'a,
+    A: ArchetypeAllocator + 'a,
+    F: QueryFetch,
+    I: Iterator<Item = &'a Archetype<A>>,
+> {
+    archetypes: I,
+    change_tick: u32,
+    _phantom: PhantomData<fn() -> F>,
+}
+
+impl<'a, A: ArchetypeAllocator + 'a, F: QueryFetch, I: Iterator<Item = &'a Archetype<A>>>
+    QueryChunkIter<'a, A, F, I>
+{
+    /// Creates a new query chunk iterator.
+    /// Mutably accessed columns are marked as changed with [`change_tick`].
+    /// # Safety
+    /// - All archetypes yielded by the inner iterator must match [`QueryFetch::DESCRIPTOR`].
+    /// - Mutable slices may only be fetched if the archetypes are exclusively borrowed for 'a.
+    pub(crate) unsafe fn new(archetypes: I, change_tick: u32) -> Self {
+        Self {
+            archetypes,
+            change_tick,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, A: ArchetypeAllocator + 'a, F: QueryFetch, I: Iterator<Item = &'a Archetype<A>>> Iterator
+    for QueryChunkIter<'a, A, F, I>
+{
+    type Item = (&'a [Entity], F::Slice<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let archetype = self.archetypes.next()?;
+        let columns = F::columns(archetype);
+        unsafe {
+            Some((
+                archetype.entities(),
+                F::slices(archetype, &columns, self.change_tick),
+            ))
+        }
+    }
+}
+
+impl<'a, A: ArchetypeAllocator + 'a, F: QueryFetch, I: FusedIterator<Item = &'a Archetype<A>>>
+    FusedIterator for QueryChunkIter<'a, A, F, I>
+{
+}
@@ -0,0 +1,116 @@
+mod fetch;
+mod filter;
+mod iterators;
+
+#[cfg(test)]
+mod tests;
+
+pub use fetch::*;
+pub use filter::*;
+pub(crate) use iterators::*;
+
+use crate::allocator::ArchetypeAllocator;
+use crate::archetype_registry::ArchetypeRegistry;
+use crate::{Entity, Registry};
+use core::marker::PhantomData;
+#[cfg(feature = "change_detection")]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A typed query over the components in a registry.
+/// [`F`] specifies the fetched terms, e.g. `(Read<A>, Write<B>, Option<C>)`.
+/// [`R`] specifies the archetype filter, e.g. `(With<D>, Without<E>)`.
+/// A query matches nothing if a component type is fetched more than once or if all fetched terms are optional.
+///
+/// With the `change_detection` feature, each iteration is a run of the query.
+/// Filters such as `Changed<C>` only match components changed since the previous run,
+/// so a query should only be used with a single registry.
+pub struct Query<F: QueryFetch, R: QueryFilter = ()> {
+    #[cfg(feature = "change_detection")]
+    last_run: AtomicU32,
+    _phantom: PhantomData<fn() -> (F, R)>,
+}
+
+impl<F: QueryFetch, R: QueryFilter> Query<F, R> {
+    /// Creates a new query.
+    /// The first run of the query matches all components added or changed since the registry was created.
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "change_detection")]
+            last_run: AtomicU32::new(u32::MAX),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator which iterates over all entities matching the query and their fetched components.
+    pub fn iter<'registry, A: ArchetypeAllocator>(
+        &self,
+        registry: &'registry Registry<A>,
+    ) -> impl Iterator<Item = (Entity, F::Item<'registry>)> + 'registry
+    where
+        F: ReadOnlyQueryFetch,
+    {
+        let (last_run, this_run) = self.run(registry.archetypes());
+        registry.archetypes().iter_query::<F, R>(last_run, this_run)
+    }
+
+    /// Returns an iterator which mutably iterates over all entities matching the query and their fetched components.
+    pub fn iter_mut<'registry, A: ArchetypeAllocator>(
+        &self,
+        registry: &'registry mut Registry<A>,
+    ) -> impl Iterator<Item = (Entity, F::Item<'registry>)> + 'registry {
+        let (last_run, this_run) = self.run(registry.archetypes());
+        registry
+            .archetypes_mut()
+            .iter_query_mut::<F, R>(last_run, this_run)
+    }
+
+    /// Returns an iterator which iterates over all archetypes matching the query,
+    /// yielding their entities and fetched component slices.
+    /// Only archetype level filters are supported, see [`ArchetypeFilter`].
+    pub fn iter_chunks<'registry, A: ArchetypeAllocator>(
+        &self,
+        registry: &'registry Registry<A>,
+    ) -> impl Iterator<Item = (&'registry [Entity], F::Slice<'registry>)> + 'registry
+    where
+        F: ReadOnlyQueryFetch,
+        R: ArchetypeFilter,
+    {
+        registry.archetypes().iter_query_chunks::<F, R>()
+    }
+
+    /// Returns an iterator which mutably iterates over all archetypes matching the query,
+    /// yielding their entities and fetched component slices.
+    /// Only archetype level filters are supported, see [`ArchetypeFilter`].
+    /// Columns fetched by [`Write<C>`] are marked as changed once they are mutably accessed, see [`ColumnMut`].
+    pub fn iter_chunks_mut<'registry, A: ArchetypeAllocator>(
+        &self,
+        registry: &'registry mut Registry<A>,
+    ) -> impl Iterator<Item = (&'registry [Entity], F::Slice<'registry>)> + 'registry
+    where
+        R: ArchetypeFilter,
+    {
+        let (_, this_run) = self.run(registry.archetypes());
+        registry
+            .archetypes_mut()
+            .iter_query_chunks_mut::<F, R>(this_run)
+    }
+
+    /// Starts a new run of the query, returning the change ticks of the previous and the current run.
+    #[cfg(feature = "change_detection")]
+    fn run<A: ArchetypeAllocator>(&self, archetypes: &ArchetypeRegistry<A>) -> (u32, u32) {
+        let this_run = archetypes.advance_change_tick();
+        (self.last_run.swap(this_run, Ordering::Relaxed), this_run)
+    }
+
+    #[cfg(not(feature = "change_detection"))]
+    fn run<A: ArchetypeAllocator>(&self, archetypes: &ArchetypeRegistry<A>) -> (u32, u32) {
+        let change_tick = archetypes.change_tick();
+        (change_tick, change_tick)
+    }
+}
+
+impl<F: QueryFetch, R: QueryFilter> Default for Query<F, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,142 @@
+use crate::test_components::*;
+use crate::*;
+
+fn populate_registry(registry: &mut Registry) {
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap();
+    }
+    registry
+        .create_entity((A::default(), B::default(), C::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), B::default(), DropLogA::default()))
+        .unwrap();
+    registry
+        .create_entity((
+            A::default(),
+            B::default(),
+            C::default(),
+            DropLogA::default(),
+        ))
+        .unwrap();
+    registry
+        .create_entity((
+            A::default(),
+            B::default(),
+            DropLogA::default(),
+            DropLogB::default(),
+        ))
+        .unwrap();
+    registry.create_entity(A::default()).unwrap();
+}
+
+#[test]
+fn test_query_iter() {
+    let mut registry = Registry::default();
+    populate_registry(&mut registry);
+
+    let query = Query::<(Read<A>, Read<B>)>::new();
+    assert_eq!(query.iter(&registry).count(), 7);
+    for (entity, (a, b)) in query.iter(&registry) {
+        assert_eq!(registry.get_component::<A>(entity), Some(a));
+        assert_eq!(registry.get_component::<B>(entity), Some(b));
+    }
+
+    let query = Query::<Read<A>>::new();
+    assert_eq!(query.iter(&registry).count(), 8);
+
+    let query = Query::<(Read<A>, Option<C>)>::new();
+    assert_eq!(query.iter(&registry).count(), 8);
+    assert_eq!(
+        query
+            .iter(&registry)
+            .filter(|(_, (_, c))| c.is_some())
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_query_iter_filtered() {
+    let mut registry = Registry::default();
+    populate_registry(&mut registry);
+
+    let query = Query::<(Read<A>, Write<B>, Option<C>), (With<DropLogA>, Without<DropLogB>)>::new();
+    for (entity, (_, b, c)) in query.iter_mut(&mut registry) {
+        assert!(entity.is_valid());
+        b._data = match c {
+            Some(_) => 20,
+            None => 10,
+        };
+    }
+
+    let query = Query::<(Read<B>, Option<C>)>::new();
+    let mut changed = query
+        .iter(&registry)
+        .filter(|(_, (b, _))| b._data != B::default()._data)
+        .map(|(_, (b, c))| (b._data, c.is_some()))
+        .collect::<alloc::vec::Vec<_>>();
+    changed.sort();
+    assert_eq!(changed, [(10, false), (20, true)]);
+
+    let query = Query::<Read<A>, Without<B>>::new();
+    assert_eq!(query.iter(&registry).count(), 1);
+    let query = Query::<Read<A>, (With<C>, With<DropLogA>)>::new();
+    assert_eq!(query.iter(&registry).count(), 1);
+}
+
+#[test]
+fn test_query_iter_chunks() {
+    let mut registry = Registry::default();
+    populate_registry(&mut registry);
+
+    let query = Query::<(Write<A>, Option<Write<C>>), Without<DropLogA>>::new();
+    let mut entity_count = 0;
+    for (entities, (a, c)) in query.iter_chunks_mut(&mut registry) {
+        assert_eq!(entities.len(), a.len());
+        if let Some(mut c) = c {
+            assert_eq!(entities.len(), c.len());
+            c.iter_mut().for_each(|c| c._data = 30);
+        }
+        entity_count += entities.len();
+    }
+    assert_eq!(entity_count, 5);
+
+    let query = Query::<Read<C>>::new();
+    let mut entity_count = 0;
+    for (entities, c) in query.iter_chunks(&registry) {
+        for (entity, c) in entities.iter().zip(c) {
+            match registry.has_component::<DropLogA>(*entity) {
+                true => assert_eq!(c._data, C::default()._data),
+                false => assert_eq!(c._data, 30),
+            }
+        }
+        entity_count += entities.len();
+    }
+    assert_eq!(entity_count, 2);
+}
+
+#[test]
+fn test_query_invalid() {
+    let mut registry = Registry::default();
+    populate_registry(&mut registry);
+
+    assert!(!<(Read<A>, Write<A>) as QueryFetch>::DESCRIPTOR.is_valid());
+    assert!(!<(Read<A>, Option<A>) as QueryFetch>::DESCRIPTOR.is_valid());
+    assert!(!<Option<A> as QueryFetch>::DESCRIPTOR.is_valid());
+    assert!(<(Option<A>, Read<B>) as QueryFetch>::DESCRIPTOR.is_valid());
+    assert_eq!(
+        <(Read<B>, Option<C>, Write<A>) as QueryFetch>::DESCRIPTOR.archetype_id(),
+        <(A, B) as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .archetype_id()
+    );
+
+    let query = Query::<(Read<A>, Write<A>)>::new();
+    assert_eq!(query.iter_mut(&mut registry).count(), 0);
+    let query = Query::<Option<A>>::new();
+    assert_eq!(query.iter(&registry).count(), 0);
+    assert_eq!(query.iter_chunks(&registry).count(), 0);
+}
@@ -0,0 +1,65 @@
+use crate::descriptors::component_descriptor::{same_component_name, ComponentDescriptor};
+use crate::descriptors::component_type_id::ComponentTypeId;
+use alloc::collections::BTreeMap;
+
+/// The name and layout of a component type, as recorded the first time it is seen.
+#[derive(Debug, Clone, Copy)]
+struct ComponentTypeInfo {
+    name: &'static str,
+    size: u16,
+    align: u16,
+}
+
+impl ComponentTypeInfo {
+    fn matches(&self, descriptor: &ComponentDescriptor) -> bool {
+        same_component_name(self.name, descriptor.name())
+            && self.size == descriptor.size()
+            && self.align == descriptor.align()
+    }
+}
+
+impl From<&ComponentDescriptor> for ComponentTypeInfo {
+    fn from(descriptor: &ComponentDescriptor) -> Self {
+        Self {
+            name: descriptor.name(),
+            size: descriptor.size(),
+            align: descriptor.align(),
+        }
+    }
+}
+
+/// Records the name and layout of each component type seen by a registry,
+/// detecting distinct component types which share a component type id.
+#[derive(Debug, Default)]
+pub(crate) struct ComponentTypeTable {
+    types: BTreeMap<ComponentTypeId, ComponentTypeInfo>,
+}
+
+impl ComponentTypeTable {
+    /// Records the given component types.
+    /// Returns the names of the previously recorded and the given component type on the first collision,
+    /// in which case none of the given component types are recorded.
+    pub(crate) fn record(
+        &mut self,
+        components: &[ComponentDescriptor],
+    ) -> Result<(), (&'static str, &'static str)> {
+        let mut unseen = false;
+        for component in components {
+            match self.types.get(&component.component_type_id()) {
+                Some(recorded) if !recorded.matches(component) => {
+                    return Err((recorded.name, component.name()));
+                }
+                Some(_) => {}
+                None => unseen = true,
+            }
+        }
+        if unseen {
+            for component in components {
+                self.types
+                    .entry(component.component_type_id())
+                    .or_insert_with(|| component.into());
+            }
+        }
+        Ok(())
+    }
+}
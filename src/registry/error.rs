@@ -0,0 +1,37 @@
+/// Describes why a fallible registry operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegistryErrorKind {
+    /// The provided entity is invalid or no longer alive.
+    InvalidEntity,
+    /// The component group is invalid, or conflicts with the components already present on the entity.
+    InvalidComponentGroup,
+    /// An internal limit is exceeded, such as the maximum amount of entities, components or archetypes.
+    LimitExceeded,
+    /// The backing memory of an archetype could not be allocated.
+    AllocationFailed,
+    /// Two distinct component types share the same component type id.
+    /// Contains the names of the previously seen and the newly provided component type.
+    ComponentTypeCollision {
+        existing: &'static str,
+        provided: &'static str,
+    },
+}
+
+/// Error returned by the fallible registry operations.
+/// Hands the components which could not be stored back to the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryError<T> {
+    pub kind: RegistryErrorKind,
+    pub components: T,
+}
+
+impl<T> RegistryError<T> {
+    pub(crate) const fn new(kind: RegistryErrorKind, components: T) -> Self {
+        Self { kind, components }
+    }
+
+    /// Returns the components which could not be stored, discarding the reason of failure.
+    pub fn into_components(self) -> T {
+        self.components
+    }
+}
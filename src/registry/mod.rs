@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests;
 
+mod component_types;
+mod error;
 mod registry;
+mod removal_log;
 
+pub use error::*;
 pub use registry::*;
@@ -1,27 +1,279 @@
-use crate::ArchetypeIndex;
+use super::component_types::ComponentTypeTable;
+use super::removal_log::RemovalLog;
+use crate::allocator::{ArchetypeAllocator, Global};
 use crate::archetype::Archetype;
 use crate::archetype_descriptor::ArchetypeDescriptor;
 use crate::archetype_registry::ArchetypeRegistry;
+use crate::component_descriptor::ComponentDescriptor;
 use crate::descriptors::component_group::ComponentGroup;
-use crate::entity_registry::IndexInArchetype;
+use crate::entity_registry::{EntityEntry, EntityReserver, IndexInArchetype};
+use crate::ArchetypeIndex;
+use crate::CommandBuffer;
+use crate::MAX_ENTITIES_PER_ARCHETYPE;
 use crate::{entity_registry::EntityRegistry, Component, Entity};
+use crate::{RegistryError, RegistryErrorKind};
+use alloc::vec::Vec;
 
 /// The primary construct in the *Shard* Entity Component System (ECS).
-#[derive(Default)]
-pub struct Registry {
+/// The memory of archetypes is allocated using [`A`], which defaults to the global allocator.
+pub struct Registry<A: ArchetypeAllocator = Global> {
     entities: EntityRegistry,
-    archetypes: ArchetypeRegistry,
+    archetypes: ArchetypeRegistry<A>,
+    removal_log: RemovalLog,
+    component_types: ComponentTypeTable,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new_in(Global)
+    }
 }
 
-impl Registry {
+impl<A: ArchetypeAllocator> Registry<A> {
+    /// Creates an empty registry which allocates the memory of its archetypes using the given allocator.
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            entities: EntityRegistry::default(),
+            archetypes: ArchetypeRegistry::new_in(allocator),
+            removal_log: RemovalLog::default(),
+            component_types: ComponentTypeTable::default(),
+        }
+    }
+
     /// Creates a new entity using the provided components.
     /// Returns Ok with a Entity if successful, or Err(components) if not.
-    /// Returns Err if the provided component group is invalid or an internal limit is exceeded.
+    /// Returns Err if a component type collides with a previously seen one or an internal limit is exceeded.
     /// Panics in case of allocation failure.
     pub fn create_entity<'c, G: ComponentGroup>(&mut self, components: G) -> Result<Entity, G> {
-        if !G::DESCRIPTOR.is_valid() {
+        self.create_entity_with(
+            components,
+            |entities, index_in_archetype, archetype_index| {
+                entities.create_entity(index_in_archetype, archetype_index)
+            },
+        )
+    }
+
+    /// Creates a new entity using the provided components.
+    /// Returns Ok with a Entity if successful, or Err with the reason of failure and the components if not.
+    /// Unlike [`Registry::create_entity`], returns Err instead of panicking if archetype growth fails.
+    pub fn try_create_entity<G: ComponentGroup>(
+        &mut self,
+        components: G,
+    ) -> Result<Entity, RegistryError<G>> {
+        if let Err(kind) = self.record_component_types(G::DESCRIPTOR.archetype().components()) {
+            return Err(RegistryError::new(kind, components));
+        }
+        let change_tick = self.archetypes.change_tick();
+        let (archetype_index, archetype) = match self
+            .archetypes
+            .try_find_or_create_archetype(G::DESCRIPTOR.archetype())
+        {
+            Ok(v) => v,
+            Err(kind) => return Err(RegistryError::new(kind, components)),
+        };
+        if let Err(kind) = Self::try_make_room(archetype) {
+            return Err(RegistryError::new(kind, components));
+        }
+        let index_in_archetype = IndexInArchetype::new(archetype.len()).unwrap();
+        let entity = match self.entities.try_create_entity(
+            index_in_archetype,
+            ArchetypeIndex::new(archetype_index).unwrap(),
+        ) {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                return Err(RegistryError::new(
+                    RegistryErrorKind::LimitExceeded,
+                    components,
+                ))
+            }
+            Err(_) => {
+                return Err(RegistryError::new(
+                    RegistryErrorKind::AllocationFailed,
+                    components,
+                ))
+            }
+        };
+        unsafe {
+            let index = archetype.push_entity_unchecked(entity, components);
+            archetype.mark_added_unchecked(index, change_tick);
+        }
+        Ok(entity)
+    }
+
+    /// Creates a new entity for each component group yielded by the given iterator.
+    /// The archetype is looked up once, its capacity and the entity slots are allocated in bulk using the size hint.
+    /// Returns an iterator over the created entities, in the order of the given component groups.
+    /// If a component type collides with a previously seen one or an internal limit is exceeded, the remaining component groups are dropped.
+    /// Panics in case of allocation failure.
+    pub fn spawn_batch<G: ComponentGroup, I: IntoIterator<Item = G>>(
+        &mut self,
+        components: I,
+    ) -> impl Iterator<Item = Entity> {
+        let mut spawned = Vec::new();
+        if self
+            .record_component_types(G::DESCRIPTOR.archetype().components())
+            .is_err()
+        {
+            return spawned.into_iter();
+        }
+        let change_tick = self.archetypes.change_tick();
+        let (archetype_index, archetype) = match self
+            .archetypes
+            .find_or_create_archetype(G::DESCRIPTOR.archetype())
+        {
+            Some(v) => v,
+            None => return spawned.into_iter(),
+        };
+        let archetype_index = ArchetypeIndex::new(archetype_index).unwrap();
+        let mut components = components.into_iter();
+        spawned.reserve(components.size_hint().0);
+        while let Some(first) = components.next() {
+            // Matches the capacity limit of archetypes, see `try_make_room`.
+            let available = (MAX_ENTITIES_PER_ARCHETYPE - 1).saturating_sub(archetype.len());
+            let count = (components.size_hint().0 as u64 + 1).min(available as u64) as u32;
+            if count == 0 {
+                break;
+            }
+            archetype.reserve(count);
+            let entities = self
+                .entities
+                .create_entities(count, archetype.len(), archetype_index);
+            let created = entities.len();
+            // Entities which are not filled, because the iterator ends early or panics, are destroyed again.
+            let mut batch_entities = UnfilledEntities {
+                registry: &mut self.entities,
+                entities,
+                filled: 0,
+            };
+            let mut batch = core::iter::once(first).chain(components.by_ref());
+            while batch_entities.filled < created {
+                // The iterator may yield less than its size hint promised.
+                let group = match batch.next() {
+                    Some(v) => v,
+                    None => break,
+                };
+                let entity = batch_entities.entities[batch_entities.filled];
+                unsafe {
+                    let index = archetype.push_entity_unchecked(entity, group);
+                    archetype.mark_added_unchecked(index, change_tick);
+                }
+                batch_entities.filled += 1;
+                spawned.push(entity);
+            }
+            drop(batch_entities);
+            if created < count as usize {
+                break;
+            }
+        }
+        spawned.into_iter()
+    }
+
+    /// Creates a new entity without any components.
+    /// Returns None if an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn create_empty_entity(&mut self) -> Option<Entity> {
+        self.create_empty_entity_with(|entities, index_in_archetype, archetype_index| {
+            entities.create_entity(index_in_archetype, archetype_index)
+        })
+    }
+
+    /// Creates a new entity without any components using the entity allocated by [`allocate`].
+    fn create_empty_entity_with(
+        &mut self,
+        allocate: impl FnOnce(&mut EntityRegistry, IndexInArchetype, ArchetypeIndex) -> Option<Entity>,
+    ) -> Option<Entity> {
+        let archetype = unsafe {
+            self.archetypes
+                .get_unchecked_mut(ArchetypeRegistry::<A>::EMPTY_ARCHETYPE_INDEX)
+        };
+        let entity = allocate(
+            &mut self.entities,
+            IndexInArchetype::new(archetype.len())?,
+            ArchetypeIndex::new(ArchetypeRegistry::<A>::EMPTY_ARCHETYPE_INDEX)?,
+        )?;
+        unsafe {
+            let index = archetype.push_uninitialized_entity();
+            archetype.entities_mut()[index as usize] = entity;
+        }
+        Some(entity)
+    }
+
+    /// Reserves an entity handle without requiring mutable access to the registry.
+    /// Reuses the slot of a previously destroyed entity if possible, can be called from multiple threads at once.
+    /// The entity is created once it is flushed using [`Registry::flush_reserved`] or [`Registry::flush_reserved_empty`].
+    /// Returns the invalid entity if the maximum amount of entities is exceeded.
+    pub fn reserve_entity(&self) -> Entity {
+        self.entities.reserve_entity().unwrap_or(Entity::INVALID)
+    }
+
+    /// Creates all reserved entities which have not been created yet, including those
+    /// reserved by command buffers which have not been applied yet or were dropped.
+    /// Reservations of dropped command buffers are never released otherwise, so their slots can only be reused
+    /// after flushing and destroying those entities.
+    /// The components of each entity are provided by the given closure.
+    /// Reserved entities which could not be created are released and remain invalid.
+    pub fn flush_reserved<G: ComponentGroup>(&mut self, mut components: impl FnMut(Entity) -> G) {
+        for entity in self.entities.reserved_entities() {
+            let _ = self.create_reserved_entity(entity, components(entity));
+        }
+    }
+
+    /// Creates all reserved entities which have not been created yet without any components,
+    /// including those reserved by command buffers which have not been applied yet or were dropped.
+    /// See [`Registry::flush_reserved`].
+    /// Reserved entities which could not be created are released and remain invalid.
+    pub fn flush_reserved_empty(&mut self) {
+        for entity in self.entities.reserved_entities() {
+            let created =
+                self.create_empty_entity_with(|entities, index_in_archetype, archetype_index| {
+                    entities
+                        .create_reserved_entity(entity, index_in_archetype, archetype_index)
+                        .then_some(entity)
+                });
+            if created.is_none() {
+                self.entities.release_reserved_entity(entity);
+            }
+        }
+    }
+
+    /// Creates the reserved entity using the provided components.
+    /// The reserved handle is released if the entity could not be created.
+    /// Returns Err(components) if the handle is not reserved or the entity could not be created.
+    pub(crate) fn create_reserved_entity<G: ComponentGroup>(
+        &mut self,
+        entity: Entity,
+        components: G,
+    ) -> Result<(), G> {
+        let result = self.create_entity_with(
+            components,
+            |entities, index_in_archetype, archetype_index| {
+                entities
+                    .create_reserved_entity(entity, index_in_archetype, archetype_index)
+                    .then_some(entity)
+            },
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(components) => {
+                self.entities.release_reserved_entity(entity);
+                Err(components)
+            }
+        }
+    }
+
+    /// Creates a new entity using the provided components and the entity allocated by [`allocate`].
+    fn create_entity_with<G: ComponentGroup>(
+        &mut self,
+        components: G,
+        allocate: impl FnOnce(&mut EntityRegistry, IndexInArchetype, ArchetypeIndex) -> Option<Entity>,
+    ) -> Result<Entity, G> {
+        if self
+            .record_component_types(G::DESCRIPTOR.archetype().components())
+            .is_err()
+        {
             return Err(components);
         }
+        let change_tick = self.archetypes.change_tick();
         let (archetype_index, archetype) = match self
             .archetypes
             .find_or_create_archetype(G::DESCRIPTOR.archetype())
@@ -30,11 +282,18 @@ impl Registry {
             None => return Err(components),
         };
         let index_in_archetype = IndexInArchetype::new(archetype.len()).unwrap();
-        let entity = match self.entities.create_entity(index_in_archetype, ArchetypeIndex::new(archetype_index).unwrap()) {
+        let entity = match allocate(
+            &mut self.entities,
+            index_in_archetype,
+            ArchetypeIndex::new(archetype_index).unwrap(),
+        ) {
             Some(v) => v,
             None => return Err(components),
         };
-        unsafe { archetype.push_entity_unchecked(entity, components) };
+        unsafe {
+            let index = archetype.push_entity_unchecked(entity, components);
+            archetype.mark_added_unchecked(index, change_tick);
+        }
         Ok(entity)
     }
 
@@ -47,6 +306,8 @@ impl Registry {
         };
         let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
         let index_in_archetype = entry.index_in_archetype();
+        self.removal_log
+            .record_destroyed(entity, archetype.descriptor());
         unsafe {
             if archetype.swap_drop_unchecked(index_in_archetype.value()) {
                 // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
@@ -65,6 +326,50 @@ impl Registry {
         true
     }
 
+    /// Destroys all entities in archetypes matching the specified component group.
+    /// Matching archetypes are cleared at once, keeping their allocated memory.
+    /// Returns the amount of destroyed entities.
+    pub fn destroy_matching<G: ComponentGroup>(&mut self) -> usize {
+        self.destroy_filtered_matching::<G, _>(|_| true)
+    }
+
+    /// Destroys all entities in archetypes matching the specified component group.
+    /// Archetypes not matching the filter closure are excluded.
+    /// Matching archetypes are cleared at once, keeping their allocated memory.
+    /// Returns the amount of destroyed entities.
+    pub fn destroy_filtered_matching<G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>(
+        &mut self,
+        filter_closure: F,
+    ) -> usize {
+        let mut destroyed = 0;
+        for archetype in self
+            .archetypes
+            .iter_filtered_archetypes_matching_mut::<G, F>(filter_closure)
+        {
+            for entity in archetype.entities() {
+                self.removal_log
+                    .record_destroyed(*entity, archetype.descriptor());
+            }
+            self.entities.destroy_entities(archetype.entities());
+            destroyed += archetype.len() as usize;
+            archetype.clear();
+        }
+        destroyed
+    }
+
+    /// Destroys all entities, keeping the memory allocated by the archetypes for reuse.
+    /// Handles of destroyed entities remain invalid, reserved entities which have not been created yet are kept.
+    pub fn clear(&mut self) {
+        for archetype in self.archetypes.iter_archetypes_mut() {
+            for entity in archetype.entities() {
+                self.removal_log
+                    .record_destroyed(*entity, archetype.descriptor());
+            }
+            archetype.clear();
+        }
+        self.entities.clear();
+    }
+
     /// Removes the entity from the registry if it matches the given component group exactly.
     /// Otherwise, it simply leaves the entity as is.
     /// This function return None if either entity given is invalid, or does not match the given component group.
@@ -75,6 +380,14 @@ impl Registry {
         };
         let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
         let index_in_archetype = entry.index_in_archetype();
+        if !archetype
+            .descriptor()
+            .has_same_components(G::DESCRIPTOR.archetype())
+        {
+            return None;
+        }
+        self.removal_log
+            .record_destroyed(entity, archetype.descriptor());
         unsafe {
             return match archetype.swap_remove_unchecked::<G>(index_in_archetype.value()) {
                 (value, true) => {
@@ -98,6 +411,34 @@ impl Registry {
         }
     }
 
+    /// Returns true if the given entity is alive, meaning it was created and not destroyed yet.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.entity_entry(entity).is_some()
+    }
+
+    /// Returns the descriptor of the archetype the given entity is stored in.
+    /// Returns None if entity is invalid.
+    pub fn archetype_of(&self, entity: Entity) -> Option<&ArchetypeDescriptor> {
+        let entry = self.entities.entity_entry(entity)?;
+        unsafe {
+            Some(
+                self.archetypes
+                    .get_unchecked(entry.archetype_index())
+                    .descriptor(),
+            )
+        }
+    }
+
+    /// Returns the index of the archetype the given entity is stored in and its index within that archetype.
+    /// Returns None if entity is invalid.
+    pub fn entity_location(&self, entity: Entity) -> Option<(ArchetypeIndex, IndexInArchetype)> {
+        let entry = self.entities.entity_entry(entity)?;
+        Some((
+            ArchetypeIndex::new(entry.archetype_index())?,
+            entry.index_in_archetype(),
+        ))
+    }
+
     /// Returns true if a given entity has the specified component.
     /// Returns false if entity is invalid or does not have the specified component.
     pub fn has_component<C: Component>(&self, entity: Entity) -> bool {
@@ -172,12 +513,15 @@ impl Registry {
             None => return None,
             Some(v) => v,
         };
+        let change_tick = self.archetypes.change_tick();
         unsafe {
             let archetype = self.archetypes.get_unchecked_mut(entry.archetype_index());
             if !archetype.descriptor().has_component::<C>() {
                 return None;
             }
-            archetype.get_component_unchecked_mut::<C>(entry.index_in_archetype().value())
+            let index = entry.index_in_archetype().value();
+            archetype.mark_component_changed_unchecked(C::ID, index, change_tick);
+            archetype.get_component_unchecked_mut::<C>(index)
         }
         .into()
     }
@@ -192,6 +536,7 @@ impl Registry {
             None => return None,
             Some(v) => v,
         };
+        let change_tick = self.archetypes.change_tick();
         unsafe {
             let archetype = self.archetypes.get_unchecked_mut(entry.archetype_index());
             if !archetype
@@ -200,7 +545,9 @@ impl Registry {
             {
                 return None;
             }
-            archetype.get_fuzzy_components_unchecked_mut::<G>(entry.index_in_archetype().value())
+            let index = entry.index_in_archetype().value();
+            archetype.mark_fuzzy_components_changed_unchecked::<G>(index, change_tick);
+            archetype.get_fuzzy_components_unchecked_mut::<G>(index)
         }
         .into()
     }
@@ -210,67 +557,69 @@ impl Registry {
     /// Reasons for failure:
     /// - Invalid entity provided.
     /// - Destination archetype could not be created.
+    ///
+    /// Panics in case of allocation failure.
     pub fn add_component<C: Component>(&mut self, entity: Entity, component: C) -> Result<(), C> {
+        self.try_add_component(entity, component)
+            .map_err(|error| match error.kind {
+                RegistryErrorKind::AllocationFailed => panic!("Archetype allocation failed."),
+                _ => error.into_components(),
+            })
+    }
+
+    /// Adds a given component to the entity if it's not yet present.
+    /// Returns Err with the reason of failure and the component if unsuccessful.
+    /// Unlike [`Registry::add_component`], returns Err instead of panicking if archetype growth fails.
+    pub fn try_add_component<C: Component>(
+        &mut self,
+        entity: Entity,
+        component: C,
+    ) -> Result<(), RegistryError<C>> {
+        if let Err(kind) = self.record_component_types(core::slice::from_ref(&C::DESCRIPTOR)) {
+            return Err(RegistryError::new(kind, component));
+        }
         let entry = match self.entities.entity_entry(entity) {
-            None => return Err(component),
+            None => {
+                return Err(RegistryError::new(
+                    RegistryErrorKind::InvalidEntity,
+                    component,
+                ))
+            }
             Some(v) => v.clone(),
         };
+        let change_tick = self.archetypes.change_tick();
 
         // Get the new archetype
         let (source_archetype, destination_archetype_index, destination_archetype) = match self
             .archetypes
-            .find_or_create_archetype_adding_component(entry.archetype_index(), &C::DESCRIPTOR)
+            .try_find_or_create_archetype_adding_component(entry.archetype_index(), &C::DESCRIPTOR)
         {
-            Some(v) => v,
-            None => return Err(component),
+            Ok(v) => v,
+            Err(kind) => return Err(RegistryError::new(kind, component)),
         };
-
-        // Make sure the entity we move is at the end of it's archetype (so data stays contiguous).
-        if unsafe { source_archetype.swap_to_last_unchecked(entry.index_in_archetype().value()) } {
-            // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
-            // We retrieve the entity handle using the metadata, which is now at the swapped with entity's position.
-            let swapped_entity =
-                source_archetype.entities()[entry.index_in_archetype().value() as usize];
-            self.entities
-                .entity_entry_mut(swapped_entity)
-                .unwrap()
-                .set_index_in_archetype(entry.index_in_archetype());
+        if let Err(kind) = Self::try_make_room(destination_archetype) {
+            return Err(RegistryError::new(kind, component));
         }
 
         unsafe {
-            // Make space in the destination archetype.
-            destination_archetype.push_uninitialized_entity();
-
-            // copy from end to end.
-            let new_source_entity_index_in_archetype = source_archetype.len() - 1;
-            let destination_entity_index_in_archetype = destination_archetype.len() - 1;
-            // Write common components.
-            Archetype::copy_common_components_between_archetypes_unchecked(
+            let (_, destination_entity_index_in_archetype) = Self::move_entity_unchecked(
+                &mut self.entities,
+                entity,
+                &entry,
                 source_archetype,
-                new_source_entity_index_in_archetype,
+                destination_archetype_index,
                 destination_archetype,
-                destination_entity_index_in_archetype,
             );
             // Write added component
             destination_archetype
                 .write_single_component_unchecked(destination_entity_index_in_archetype, component);
-
-            // Copy the metadata
-            destination_archetype.entities_mut()[destination_entity_index_in_archetype as usize] =
-                source_archetype.entities()[new_source_entity_index_in_archetype as usize];
-
-            // Make the source archetype forget the old entity.
-            source_archetype.decrement_len_unchecked();
-
-            // Update the original entity entry to point to destination archetype and index in archetype.
-            let entity_entry = self.entities.entity_entry_mut(entity).unwrap();
-            entity_entry.set_archetype_index(destination_archetype_index);
-            entity_entry.set_index_in_archetype(
-                IndexInArchetype::new(destination_entity_index_in_archetype).unwrap(),
+            destination_archetype.mark_component_added_unchecked(
+                C::ID,
+                destination_entity_index_in_archetype,
+                change_tick,
             );
-
-            Ok(())
         }
+        Ok(())
     }
 
     /// Removes a given component from the entity if it's present.
@@ -279,6 +628,12 @@ impl Registry {
     /// - Invalid entity provided.
     /// - Destination archetype could not be created.
     pub fn remove_component<C: Component>(&mut self, entity: Entity) -> Result<C, ()> {
+        if self
+            .record_component_types(core::slice::from_ref(&C::DESCRIPTOR))
+            .is_err()
+        {
+            return Err(());
+        }
         let entry = match self.entities.entity_entry(entity) {
             None => return Err(()),
             Some(v) => v.clone(),
@@ -293,61 +648,433 @@ impl Registry {
             None => return Err(()),
         };
 
+        unsafe {
+            let (source_entity_index_in_archetype, _) = Self::move_entity_unchecked(
+                &mut self.entities,
+                entity,
+                &entry,
+                source_archetype,
+                destination_archetype_index,
+                destination_archetype,
+            );
+            self.removal_log.record_removed(entity, C::ID);
+            // Read removed component
+            Ok(source_archetype.read_component_unchecked(source_entity_index_in_archetype))
+        }
+    }
+
+    /// Replaces the specified component of the entity in place with the given component.
+    /// Returns the replaced component if successful, this component is not dropped.
+    /// Returns the given component in case of failure for any reason.
+    /// Reasons for failure:
+    /// - Invalid entity provided.
+    /// - Entity does not have the specified component.
+    pub fn replace_component<C: Component>(
+        &mut self,
+        entity: Entity,
+        component: C,
+    ) -> Result<C, C> {
+        match self.get_component_mut::<C>(entity) {
+            Some(v) => Ok(core::mem::replace(v, component)),
+            None => Err(component),
+        }
+    }
+
+    /// Sets the specified component of the entity.
+    /// Writes the component in place if the entity already has it, otherwise it is added to the entity.
+    /// Returns the replaced component if there was one, or None if the component was added.
+    /// Returns the given component if it could not be added. See [`Registry::add_component`].
+    pub fn set_component<C: Component>(&mut self, entity: Entity, component: C) -> Option<C> {
+        match self.replace_component(entity, component) {
+            Ok(v) => Some(v),
+            Err(component) => self.add_component(entity, component).err(),
+        }
+    }
+
+    /// Adds the given components to the entity if none of them are present yet.
+    /// The entity is moved into its destination archetype at once, without intermediate archetypes.
+    /// Returns the original components in case of failure for any reason.
+    /// Reasons for failure:
+    /// - Invalid entity provided.
+    /// - A component type collides with a previously seen one.
+    /// - Entity already has one or more of the components.
+    /// - Destination archetype could not be created.
+    pub fn add_components<G: ComponentGroup>(
+        &mut self,
+        entity: Entity,
+        components: G,
+    ) -> Result<(), G> {
+        if self
+            .record_component_types(G::DESCRIPTOR.archetype().components())
+            .is_err()
+        {
+            return Err(components);
+        }
+        let entry = match self.entities.entity_entry(entity) {
+            None => return Err(components),
+            Some(v) => v.clone(),
+        };
+        let change_tick = self.archetypes.change_tick();
+
+        // Get the new archetype
+        let (source_archetype, destination_archetype_index, destination_archetype) = match self
+            .archetypes
+            .find_or_create_archetype_adding_components(
+                entry.archetype_index(),
+                G::DESCRIPTOR.archetype(),
+            ) {
+            Some(v) => v,
+            None => return Err(components),
+        };
+
+        unsafe {
+            let (_, destination_entity_index_in_archetype) = Self::move_entity_unchecked(
+                &mut self.entities,
+                entity,
+                &entry,
+                source_archetype,
+                destination_archetype_index,
+                destination_archetype,
+            );
+            // Write added components
+            destination_archetype
+                .write_fuzzy_components_unchecked(destination_entity_index_in_archetype, components);
+            destination_archetype.mark_fuzzy_components_added_unchecked::<G>(
+                destination_entity_index_in_archetype,
+                change_tick,
+            );
+        }
+        Ok(())
+    }
+
+    /// Removes the given components from the entity if all of them are present.
+    /// The entity is moved into its destination archetype at once, without intermediate archetypes.
+    /// Returns the components if successful.
+    /// Reasons for failure:
+    /// - Invalid entity provided.
+    /// - A component type collides with a previously seen one.
+    /// - Entity does not have all of the components.
+    /// - Destination archetype could not be created.
+    pub fn remove_components<G: ComponentGroup>(&mut self, entity: Entity) -> Result<G, ()> {
+        if self
+            .record_component_types(G::DESCRIPTOR.archetype().components())
+            .is_err()
+        {
+            return Err(());
+        }
+        let entry = match self.entities.entity_entry(entity) {
+            None => return Err(()),
+            Some(v) => v.clone(),
+        };
+
+        // Get the new archetype
+        let (source_archetype, destination_archetype_index, destination_archetype) = match self
+            .archetypes
+            .find_or_create_archetype_removing_components(
+                entry.archetype_index(),
+                G::DESCRIPTOR.archetype(),
+            ) {
+            Some(v) => v,
+            None => return Err(()),
+        };
+
+        unsafe {
+            let (source_entity_index_in_archetype, _) = Self::move_entity_unchecked(
+                &mut self.entities,
+                entity,
+                &entry,
+                source_archetype,
+                destination_archetype_index,
+                destination_archetype,
+            );
+            self.removal_log
+                .record_removed_all(entity, G::DESCRIPTOR.archetype());
+            // Read removed components
+            Ok(source_archetype.read_fuzzy_components_unchecked(source_entity_index_in_archetype))
+        }
+    }
+
+    /// Records the given component types the first time they are seen.
+    /// Fails if one of them shares its component type id with a different, previously seen component type.
+    /// Panics in that case in debug builds, naming both component types.
+    fn record_component_types(
+        &mut self,
+        components: &[ComponentDescriptor],
+    ) -> Result<(), RegistryErrorKind> {
+        self.component_types
+            .record(components)
+            .map_err(|(existing, provided)| {
+                if cfg!(debug_assertions) {
+                    panic!(
+                        "Component types `{}` and `{}` share the same component type id.",
+                        existing, provided
+                    );
+                }
+                RegistryErrorKind::ComponentTypeCollision { existing, provided }
+            })
+    }
+
+    /// Makes room for a single additional entity in the archetype.
+    /// Fails if the archetype is at its entity limit or its backing memory could not be grown.
+    fn try_make_room(archetype: &mut Archetype<A>) -> Result<(), RegistryErrorKind> {
+        if archetype.len() >= MAX_ENTITIES_PER_ARCHETYPE - 1 {
+            return Err(RegistryErrorKind::LimitExceeded);
+        }
+        archetype
+            .try_resize_if_necessary()
+            .map_err(|_| RegistryErrorKind::AllocationFailed)
+    }
+
+    /// Moves an entity from its source archetype to the end of the destination archetype.
+    /// Components present in both archetypes are copied and the entity entries are updated.
+    /// Returns the index the entity's data had in the source archetype and the index
+    /// of the entity in the destination archetype.
+    /// # Safety
+    /// - [`entry`] must be the entry of [`entity`] and point into [`source_archetype`].
+    /// - Components only present in the destination archetype are left uninitialized
+    ///   and must be written by the caller.
+    /// - Components only present in the source archetype are left past the end of the source archetype.
+    ///   These must be read by the caller before the source archetype is modified again.
+    unsafe fn move_entity_unchecked(
+        entities: &mut EntityRegistry,
+        entity: Entity,
+        entry: &EntityEntry,
+        source_archetype: &mut Archetype<A>,
+        destination_archetype_index: u16,
+        destination_archetype: &mut Archetype<A>,
+    ) -> (u32, u32) {
         // Make sure the entity we move is at the end of it's archetype (so data stays contiguous).
-        if unsafe { source_archetype.swap_to_last_unchecked(entry.index_in_archetype().value()) } {
+        if source_archetype.swap_to_last_unchecked(entry.index_in_archetype().value()) {
             // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
             // We retrieve the entity handle using the metadata, which is now at the swapped with entity's position.
             let swapped_entity =
                 source_archetype.entities()[entry.index_in_archetype().value() as usize];
-            self.entities
+            entities
                 .entity_entry_mut(swapped_entity)
                 .unwrap()
                 .set_index_in_archetype(entry.index_in_archetype());
         }
 
+        // Make space in the destination archetype.
+        destination_archetype.push_uninitialized_entity();
+
+        // copy from end to end.
+        let new_source_entity_index_in_archetype = source_archetype.len() - 1;
+        let destination_entity_index_in_archetype = destination_archetype.len() - 1;
+        // Write common components.
+        Archetype::copy_common_components_between_archetypes_unchecked(
+            source_archetype,
+            new_source_entity_index_in_archetype,
+            destination_archetype,
+            destination_entity_index_in_archetype,
+        );
+
+        // Copy the metadata
+        destination_archetype.entities_mut()[destination_entity_index_in_archetype as usize] =
+            source_archetype.entities()[new_source_entity_index_in_archetype as usize];
+
+        // Make the source archetype forget the old entity.
+        source_archetype.decrement_len_unchecked();
+
+        // Update the original entity entry to point to destination archetype and index in archetype.
+        let entity_entry = entities.entity_entry_mut(entity).unwrap();
+        entity_entry.set_archetype_index(destination_archetype_index);
+        entity_entry.set_index_in_archetype(
+            IndexInArchetype::new(destination_entity_index_in_archetype).unwrap(),
+        );
+
+        (
+            new_source_entity_index_in_archetype,
+            destination_entity_index_in_archetype,
+        )
+    }
+}
+
+impl<A: ArchetypeAllocator> Registry<A> {
+    /// Returns the current change tick of the registry.
+    /// Added and changed components are marked with the change tick at the time of access.
+    #[cfg(feature = "change_detection")]
+    pub fn change_tick(&self) -> u32 {
+        self.archetypes.change_tick()
+    }
+
+    /// Increments the change tick of the registry, wrapping around on overflow.
+    /// Not required for change detection: each [`crate::Query`] advances the change tick whenever it runs,
+    /// and reports the components added or changed since its own previous run.
+    /// Only separates the ticks of accesses before and after the call, see [`Registry::get_component_ticks`].
+    #[cfg(feature = "change_detection")]
+    pub fn increment_change_tick(&mut self) {
+        self.archetypes.increment_change_tick();
+    }
+
+    /// Returns the ticks of the specified component if the entity has it.
+    #[cfg(feature = "change_detection")]
+    pub fn get_component_ticks<C: Component>(
+        &self,
+        entity: Entity,
+    ) -> Option<crate::archetype::ComponentTicks> {
+        let entry = self.entities.entity_entry(entity)?;
         unsafe {
-            // Make space in the destination archetype.
-            destination_archetype.push_uninitialized_entity();
-
-            // copy from end to end.
-            let new_source_entity_index_in_archetype = source_archetype.len() - 1;
-            let destination_entity_index_in_archetype = destination_archetype.len() - 1;
-            // Write common components.
-            Archetype::copy_common_components_between_archetypes_unchecked(
-                source_archetype,
-                new_source_entity_index_in_archetype,
-                destination_archetype,
-                destination_entity_index_in_archetype,
-            );
-            // Read removed component
-            let component: C =
-                source_archetype.read_component_unchecked(new_source_entity_index_in_archetype);
+            self.archetypes
+                .get_unchecked(entry.archetype_index())
+                .component_ticks(C::ID, entry.index_in_archetype().value())
+        }
+    }
 
-            // Copy the metadata
-            destination_archetype.entities_mut()[destination_entity_index_in_archetype as usize] =
-                source_archetype.entities()[new_source_entity_index_in_archetype as usize];
+    /// Returns whether component removals and entity destructions are recorded.
+    pub fn is_removal_log_enabled(&self) -> bool {
+        self.removal_log.is_enabled()
+    }
 
-            // Make the source archetype forget the old entity.
-            source_archetype.decrement_len_unchecked();
+    /// Enables or disables recording of component removals and entity destructions.
+    /// Disabling the log clears all recorded events. The log is disabled by default.
+    pub fn set_removal_log_enabled(&mut self, enabled: bool) {
+        self.removal_log.set_enabled(enabled);
+    }
 
-            // Update the original entity entry to point to destination archetype and index in archetype.
-            let entity_entry = self.entities.entity_entry_mut(entity).unwrap();
-            entity_entry.set_archetype_index(destination_archetype_index);
-            entity_entry.set_index_in_archetype(
-                IndexInArchetype::new(destination_entity_index_in_archetype).unwrap(),
-            );
+    /// Returns an iterator draining all entities from which the specified component was removed,
+    /// either explicitly or by destroying the entity, since it was last drained.
+    /// Entities may no longer be valid and are yielded in order of removal.
+    pub fn drain_removed<C: Component>(&mut self) -> impl Iterator<Item = Entity> + '_ {
+        self.removal_log.drain_removed(C::ID)
+    }
 
-            Ok(component)
+    /// Returns an iterator draining all entities destroyed since it was last drained.
+    pub fn drain_destroyed(&mut self) -> impl Iterator<Item = Entity> + '_ {
+        self.removal_log.drain_destroyed()
+    }
+
+    /// Applies all commands recorded in the command buffer in order, leaving the command buffer empty.
+    /// Panics if the command buffer was created for a different registry.
+    pub fn apply(&mut self, commands: &mut CommandBuffer<A>) {
+        assert!(
+            commands
+                .reserver()
+                .shares_index_space(self.entities.reserver()),
+            "Command buffer was created for a different registry."
+        );
+        for command in commands.drain() {
+            command(self);
         }
     }
-}
 
-impl Registry {
+    /// Reserves capacity for at least [`additional`] more entities of the specified component group.
+    /// Creates the archetype of the component group if it does not exist yet.
+    /// Returns false if a component type collides with a previously seen one or the archetype could not be created.
+    /// Panics in case of allocation failure.
+    pub fn reserve<G: ComponentGroup>(&mut self, additional: u32) -> bool {
+        if self
+            .record_component_types(G::DESCRIPTOR.archetype().components())
+            .is_err()
+        {
+            return false;
+        }
+        match self
+            .archetypes
+            .find_or_create_archetype(G::DESCRIPTOR.archetype())
+        {
+            Some((_, archetype)) => archetype.reserve(additional),
+            None => return false,
+        }
+        self.entities.reserve(additional as usize);
+        true
+    }
+
+    /// Reserves capacity for at least [`additional`] more entities of the specified component group.
+    /// Creates the archetype of the component group if it does not exist yet.
+    /// Unlike [`Registry::reserve`], returns Err with the reason of failure instead of panicking if archetype growth fails.
+    pub fn try_reserve<G: ComponentGroup>(
+        &mut self,
+        additional: u32,
+    ) -> Result<(), RegistryError<()>> {
+        self.record_component_types(G::DESCRIPTOR.archetype().components())
+            .map_err(|kind| RegistryError::new(kind, ()))?;
+        let (_, archetype) = self
+            .archetypes
+            .try_find_or_create_archetype(G::DESCRIPTOR.archetype())
+            .map_err(|kind| RegistryError::new(kind, ()))?;
+        archetype
+            .try_reserve(additional)
+            .map_err(|_| RegistryError::new(RegistryErrorKind::AllocationFailed, ()))?;
+        self.entities
+            .try_reserve(additional as usize)
+            .map_err(|_| RegistryError::new(RegistryErrorKind::AllocationFailed, ()))?;
+        Ok(())
+    }
+
+    /// Shrinks the memory allocated by all archetypes and internal storage as much as possible.
+    /// Panics in case of allocation failure.
+    pub fn shrink_to_fit(&mut self) {
+        self.archetypes.shrink_to_fit();
+        self.entities.shrink_to_fit();
+    }
+
+    /// Returns an iterator over all archetypes, allowing their capacity and memory usage to be inspected.
+    pub fn iter_archetypes(&self) -> impl Iterator<Item = &Archetype<A>> + '_ {
+        self.archetypes.iter_archetypes()
+    }
+
+    /// Returns the amount of entity slots which are retired because their version is exhausted.
+    /// Retired slots are never reused, so stale entity handles never refer to a newer entity.
+    pub fn retired_slot_count(&self) -> u32 {
+        self.entities.retired_slot_count()
+    }
+
+    pub(crate) fn entity_reserver(&self) -> &EntityReserver {
+        self.entities.reserver()
+    }
+
+    pub(crate) fn archetypes(&self) -> &ArchetypeRegistry<A> {
+        &self.archetypes
+    }
+
+    pub(crate) fn archetypes_mut(&mut self) -> &mut ArchetypeRegistry<A> {
+        &mut self.archetypes
+    }
+
     /// Returns an iterator which iterates over all entities in the registry.
     pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
         self.entities.iter()
     }
 
+    /// Returns an iterator which iterates over all entities matching the specified predicate
+    /// together with references to their components.
+    pub fn iter<'registry, G: ComponentGroup>(
+        &'registry self,
+    ) -> impl ExactSizeIterator<Item = (Entity, <G as ComponentGroup>::RefTuple<'registry>)> + 'registry
+    {
+        self.archetypes.iter_components::<G>()
+    }
+
+    /// Returns an iterator which iterates over all entities matching the specified predicate
+    /// together with mutable references to their components.
+    pub fn iter_mut<'registry, G: ComponentGroup>(
+        &'registry mut self,
+    ) -> impl ExactSizeIterator<Item = (Entity, <G as ComponentGroup>::MutRefTuple<'registry>)> + 'registry
+    {
+        self.archetypes.iter_components_mut::<G>()
+    }
+
+    /// Calls the closure for every entity matching the specified predicate
+    /// with references to its components.
+    pub fn for_each<G: ComponentGroup>(
+        &self,
+        mut closure: impl FnMut(Entity, <G as ComponentGroup>::RefTuple<'_>),
+    ) {
+        self.iter::<G>()
+            .for_each(|(entity, components)| closure(entity, components));
+    }
+
+    /// Calls the closure for every entity matching the specified predicate
+    /// with mutable references to its components.
+    pub fn for_each_mut<G: ComponentGroup>(
+        &mut self,
+        mut closure: impl FnMut(Entity, <G as ComponentGroup>::MutRefTuple<'_>),
+    ) {
+        self.iter_mut::<G>()
+            .for_each(|(entity, components)| closure(entity, components));
+    }
+
     /// Returns an iterator which iterates over all components in archetypes
     /// matching the specified predicate.
     pub fn iter_components_matching<'registry, G: ComponentGroup>(
@@ -364,6 +1091,17 @@ impl Registry {
         self.archetypes.iter_components_matching_mut::<G>()
     }
 
+    /// Returns a parallel iterator which mutably iterates over all components in archetypes
+    /// matching the specified predicate.
+    /// Archetypes larger than [`crate::PARALLEL_CHUNK_SIZE`] are split into multiple chunks.
+    #[cfg(feature = "parallel")]
+    pub fn par_iter_components_matching_mut<'registry, G: ComponentGroup>(
+        &'registry mut self,
+    ) -> impl rayon::iter::ParallelIterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'registry>>
+           + 'registry {
+        self.archetypes.par_iter_components_matching_mut::<G>()
+    }
+
     /// Returns an iterator which iterates over all entities and components in archetypes
     /// matching the specified predicate.
     pub fn iter_entity_components_matching<'registry, G: ComponentGroup>(
@@ -476,11 +1214,15 @@ impl Registry {
     pub fn iter_components_exact_mut<'registry, G: ComponentGroup>(
         &'registry mut self,
     ) -> <G as ComponentGroup>::SliceMutRefTuple<'registry> {
+        let change_tick = self.archetypes.change_tick();
         match self
             .archetypes
             .find_archetype_mut(G::DESCRIPTOR.archetype())
         {
-            Some(v) => unsafe { v.get_slices_unchecked_exact_mut::<G>() },
+            Some(v) => unsafe {
+                v.mark_fuzzy_columns_changed_unchecked::<G>(change_tick);
+                v.get_slices_unchecked_exact_mut::<G>()
+            },
             None => G::empty_slice_mut(),
         }
     }
@@ -507,14 +1249,16 @@ impl Registry {
         &'registry [Entity],
         <G as ComponentGroup>::SliceMutRefTuple<'registry>,
     ) {
+        let change_tick = self.archetypes.change_tick();
         match self
             .archetypes
             .find_archetype_mut(G::DESCRIPTOR.archetype())
         {
             // Safety: entities is a separate slice, not being accessed in get_slices_unchecked_exact_mut.
             Some(v) => unsafe {
+                v.mark_fuzzy_columns_changed_unchecked::<G>(change_tick);
                 (
-                    (*(v as *mut Archetype)).entities(),
+                    (*(v as *mut Archetype<A>)).entities(),
                     v.get_slices_unchecked_exact_mut::<G>(),
                 )
             },
@@ -522,3 +1266,19 @@ impl Registry {
         }
     }
 }
+
+/// Entities created for a batch, of which only the first [`filled`] have been stored in their archetype.
+/// Destroys the remaining entities when dropped, so they never refer to uninitialized components.
+struct UnfilledEntities<'a> {
+    registry: &'a mut EntityRegistry,
+    entities: Vec<Entity>,
+    filled: usize,
+}
+
+impl<'a> Drop for UnfilledEntities<'a> {
+    fn drop(&mut self) {
+        for &entity in &self.entities[self.filled..] {
+            self.registry.destroy_entity(entity);
+        }
+    }
+}
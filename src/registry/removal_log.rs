@@ -0,0 +1,70 @@
+use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+use crate::descriptors::component_type_id::ComponentTypeId;
+use crate::Entity;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Records component removals and entity destructions if enabled.
+/// Removed components are stored per component type so they can be drained separately.
+#[derive(Debug, Default)]
+pub(crate) struct RemovalLog {
+    enabled: bool,
+    removed: BTreeMap<ComponentTypeId, Vec<Entity>>,
+    destroyed: Vec<Entity>,
+}
+
+impl RemovalLog {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the log. Disabling the log clears all recorded events.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.removed.clear();
+            self.destroyed.clear();
+        }
+    }
+
+    /// Records the removal of a single component from the entity.
+    pub(crate) fn record_removed(&mut self, entity: Entity, component_type_id: ComponentTypeId) {
+        if !self.enabled {
+            return;
+        }
+        self.removed
+            .entry(component_type_id)
+            .or_default()
+            .push(entity);
+    }
+
+    /// Records the removal of all components in the descriptor from the entity.
+    pub(crate) fn record_removed_all(&mut self, entity: Entity, descriptor: &ArchetypeDescriptor) {
+        for component in descriptor.components() {
+            self.record_removed(entity, component.component_type_id);
+        }
+    }
+
+    /// Records the destruction of the entity and the removal of all its components.
+    pub(crate) fn record_destroyed(&mut self, entity: Entity, descriptor: &ArchetypeDescriptor) {
+        if !self.enabled {
+            return;
+        }
+        self.record_removed_all(entity, descriptor);
+        self.destroyed.push(entity);
+    }
+
+    pub(crate) fn drain_removed(
+        &mut self,
+        component_type_id: ComponentTypeId,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.removed
+            .get_mut(&component_type_id)
+            .into_iter()
+            .flat_map(|entities| entities.drain(..))
+    }
+
+    pub(crate) fn drain_destroyed(&mut self) -> impl Iterator<Item = Entity> + '_ {
+        self.destroyed.drain(..)
+    }
+}
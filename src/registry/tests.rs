@@ -191,3 +191,1010 @@ fn test_registry() {
         }
     }
 }
+
+#[test]
+fn registry_test_add_remove_components() {
+    let mut registry = Registry::default();
+    let entity = registry.create_entity(B { _data: 7 }).unwrap();
+    let other = registry
+        .create_entity((A::default(), B::default(), C::default()))
+        .unwrap();
+
+    assert!(registry
+        .add_components(entity, (C { _data: 9 }, A { _data: 8 }))
+        .is_ok());
+    assert_eq!(
+        registry.get_components::<(A, B, C)>(entity),
+        Some((&A { _data: 8 }, &B { _data: 7 }, &C { _data: 9 }))
+    );
+    // Entity and other now share an archetype.
+    assert_eq!(registry.iter_components_exact::<(A, B, C)>().0.len(), 2);
+
+    // Adding components the entity already has fails and returns them.
+    let result = registry.add_components(entity, (DropLogA::default(), B::default()));
+    assert!(matches!(
+        result,
+        Err((DropLogA { _data: 4 }, B { _data: 2 }))
+    ));
+    assert!(!registry.has_component::<DropLogA>(entity));
+    assert!(registry
+        .add_components(Entity::invalid(), (A::default(), C::default()))
+        .is_err());
+
+    assert_eq!(
+        registry.remove_components::<(C, A)>(entity),
+        Ok((C { _data: 9 }, A { _data: 8 }))
+    );
+    assert!(registry.has_component::<B>(entity));
+    assert!(!registry.has_component::<A>(entity));
+    assert!(!registry.has_component::<C>(entity));
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 7 }));
+    assert_eq!(
+        registry.get_components::<(A, B, C)>(other),
+        Some((&A::default(), &B::default(), &C::default()))
+    );
+
+    // Removing components the entity does not have fails.
+    assert!(registry.remove_components::<(A, B)>(entity).is_err());
+    assert!(registry
+        .remove_components::<(A, B)>(Entity::invalid())
+        .is_err());
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 7 }));
+}
+
+#[test]
+fn registry_test_replace_set_component() {
+    let drops = DropCounter::default();
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A::default(), drops.component(1)))
+        .unwrap();
+    let other = registry.create_entity(A::default()).unwrap();
+
+    // The displaced value is handed back without being dropped by the registry.
+    let old = registry
+        .replace_component(entity, drops.component(2))
+        .unwrap();
+    assert_eq!(old._data, 1);
+    assert_eq!(drops.count(), 0);
+    drop(old);
+    assert_eq!(drops.count(), 1);
+    assert_eq!(
+        registry.get_component::<DropCounted>(entity).unwrap()._data,
+        2
+    );
+
+    // Replacing a missing component fails and hands back the given one.
+    assert_eq!(
+        registry
+            .replace_component(other, drops.component(3))
+            .unwrap_err()
+            ._data,
+        3
+    );
+    assert_eq!(drops.count(), 2);
+    assert!(!registry.has_component::<DropCounted>(other));
+
+    // Setting writes in place if present.
+    assert_eq!(
+        registry
+            .set_component(entity, drops.component(4))
+            .unwrap()
+            ._data,
+        2
+    );
+    assert_eq!(drops.count(), 3);
+    assert_eq!(
+        registry.get_component::<DropCounted>(entity).unwrap()._data,
+        4
+    );
+
+    // Setting adds the component if not present.
+    assert!(registry.set_component(other, drops.component(5)).is_none());
+    assert_eq!(drops.count(), 3);
+    assert_eq!(
+        registry.get_component::<DropCounted>(other).unwrap()._data,
+        5
+    );
+    assert_eq!(registry.get_component::<A>(other), Some(&A::default()));
+
+    // Setting on an invalid entity returns the given component.
+    assert_eq!(
+        registry
+            .set_component(Entity::invalid(), drops.component(6))
+            .unwrap()
+            ._data,
+        6
+    );
+    assert_eq!(drops.count(), 4);
+
+    // The stored values are dropped exactly once when their entities are destroyed.
+    assert!(registry.destroy_entity(entity));
+    assert!(registry.destroy_entity(other));
+    assert_eq!(drops.count(), 6);
+}
+
+#[test]
+fn registry_test_iter_for_each() {
+    let mut registry = Registry::default();
+    for i in 0..10 {
+        let entity = registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+        if i % 2 == 0 {
+            registry.add_component(entity, C::default()).unwrap();
+        }
+    }
+    registry.create_entity(A { _data: 10 }).unwrap();
+
+    let iter = registry.iter::<(B, A)>();
+    assert_eq!(iter.len(), 10);
+    let mut sum = 0;
+    for (entity, (b, a)) in iter {
+        assert_eq!(registry.get_component::<A>(entity), Some(a));
+        assert_eq!(b, &B::default());
+        sum += a._data;
+    }
+    assert_eq!(sum, 45);
+
+    {
+        let mut iter = registry.iter::<A>();
+        assert_eq!(iter.size_hint(), (11, Some(11)));
+        iter.next();
+        assert_eq!(iter.len(), 10);
+    }
+    assert_eq!(registry.iter::<(A, C)>().len(), 5);
+
+    let iter = registry.iter_mut::<(A, C)>();
+    assert_eq!(iter.len(), 5);
+    for (_, (a, c)) in iter {
+        c._data = a._data;
+    }
+
+    registry.for_each_mut::<(A, B)>(|_, (a, b)| b._data = a._data * 2);
+
+    let mut visited = 0;
+    registry.for_each::<(A, B)>(|entity, (a, b)| {
+        assert_eq!(b._data, a._data * 2);
+        if let Some(c) = registry.get_component::<C>(entity) {
+            assert_eq!(c._data, a._data);
+        }
+        visited += 1;
+    });
+    assert_eq!(visited, 10);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn registry_test_par_iter_components_matching_mut() {
+    use rayon::iter::ParallelIterator;
+
+    let mut registry = Registry::default();
+    let entity_count = PARALLEL_CHUNK_SIZE * 2 + 10;
+    for i in 0..entity_count {
+        let entity = registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+        if i % 2 == 1 {
+            registry.add_component(entity, C::default()).unwrap();
+        }
+    }
+    registry.create_entity(A::default()).unwrap();
+
+    let chunk_count = registry
+        .par_iter_components_matching_mut::<(B, A)>()
+        .map(|(b, a)| {
+            assert!(a.len() <= PARALLEL_CHUNK_SIZE);
+            for (b, a) in b.iter_mut().zip(a.iter()) {
+                b._data = a._data * 2;
+            }
+        })
+        .count();
+    assert_eq!(chunk_count, 4);
+
+    let mut visited = 0;
+    registry.for_each::<(A, B)>(|_, (a, b)| {
+        assert_eq!(b._data, a._data * 2);
+        visited += 1;
+    });
+    assert_eq!(visited, entity_count);
+}
+
+#[cfg(feature = "change_detection")]
+#[test]
+fn registry_test_change_detection() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let second = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+
+    let ticks = registry.get_component_ticks::<A>(first).unwrap();
+    assert_eq!(ticks.added, registry.change_tick());
+    assert!(registry.get_component_ticks::<C>(first).is_none());
+
+    // The first run matches everything, later runs only what changed since the previous run.
+    let changed_a = Query::<Read<A>, Changed<A>>::new();
+    let added_a = Query::<Read<A>, Added<A>>::new();
+    let added_c = Query::<Read<A>, Added<C>>::new();
+    assert_eq!(changed_a.iter(&registry).count(), 2);
+    assert_eq!(added_a.iter(&registry).count(), 2);
+    assert_eq!(added_c.iter(&registry).count(), 0);
+    assert_eq!(changed_a.iter(&registry).count(), 0);
+
+    registry.get_component_mut::<A>(second).unwrap()._data = 1;
+    registry.add_component(first, C::default()).unwrap();
+    let changed = changed_a
+        .iter(&registry)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(changed, [second]);
+    assert_eq!(changed_a.iter(&registry).count(), 0);
+    let added = added_c.iter(&registry).map(|(e, _)| e).collect::<Vec<_>>();
+    assert_eq!(added, [first]);
+    assert_eq!(added_a.iter(&registry).count(), 0);
+
+    // Changes are not missed if a query skips several change ticks.
+    registry.get_component_mut::<A>(first).unwrap()._data = 2;
+    for _ in 0..3 {
+        registry.increment_change_tick();
+    }
+    let changed = changed_a
+        .iter(&registry)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(changed, [first]);
+
+    let changed_b = Query::<Read<A>, Changed<B>>::new();
+    assert_eq!(changed_b.iter(&registry).count(), 2);
+    let query = Query::<Write<B>>::new();
+    for (entity, b) in query.iter_mut(&mut registry) {
+        if entity == first {
+            b._data = 2;
+        }
+    }
+    assert_eq!(changed_b.iter(&registry).count(), 2);
+    assert_eq!(changed_a.iter(&registry).count(), 0);
+
+    // Chunks are only marked as changed once they are mutably accessed.
+    let query = Query::<Write<B>>::new();
+    for (_, b) in query.iter_chunks_mut(&mut registry) {
+        assert_eq!(b[0]._data, 2);
+    }
+    assert_eq!(changed_b.iter(&registry).count(), 0);
+    for (_, mut b) in query.iter_chunks_mut(&mut registry) {
+        b[0]._data = 3;
+    }
+    assert_eq!(changed_b.iter(&registry).count(), 2);
+}
+
+#[test]
+fn registry_test_removal_log() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry.remove_component::<B>(first).unwrap();
+    assert_eq!(registry.drain_removed::<B>().count(), 0);
+
+    registry.set_removal_log_enabled(true);
+    assert!(registry.is_removal_log_enabled());
+    let second = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let third = registry
+        .create_entity((A::default(), C::default()))
+        .unwrap();
+
+    registry.remove_component::<B>(second).unwrap();
+    assert!(registry.destroy_entity(first));
+    assert!(registry.remove_entity::<(A, B)>(third).is_none());
+    assert!(registry.remove_entity::<(C, A)>(third).is_some());
+
+    assert_eq!(registry.drain_removed::<B>().collect::<Vec<_>>(), [second]);
+    assert_eq!(registry.drain_removed::<B>().count(), 0);
+    assert_eq!(
+        registry.drain_removed::<A>().collect::<Vec<_>>(),
+        [first, third]
+    );
+    assert_eq!(registry.drain_removed::<C>().collect::<Vec<_>>(), [third]);
+    assert_eq!(
+        registry.drain_destroyed().collect::<Vec<_>>(),
+        [first, third]
+    );
+
+    assert!(registry.destroy_entity(second));
+    registry.set_removal_log_enabled(false);
+    assert_eq!(registry.drain_removed::<A>().count(), 0);
+    assert_eq!(registry.drain_destroyed().count(), 0);
+}
+
+#[test]
+fn registry_test_reserve_entity() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A::default()).unwrap();
+    let second = registry.create_entity(A::default()).unwrap();
+    assert!(registry.destroy_entity(first));
+
+    let reused = registry.reserve_entity();
+    let extended = registry.reserve_entity();
+    assert_eq!(reused.index(), first.index());
+    assert_ne!(reused, first);
+    assert_ne!(extended.index(), second.index());
+    assert!(!registry.has_component::<A>(reused));
+
+    let mut commands = CommandBuffer::new(&registry);
+    let recorded = commands.create_entity(B::default()).unwrap();
+    let other = registry.create_entity(A::default()).unwrap();
+    assert!(![reused, extended, recorded].contains(&other));
+
+    registry.flush_reserved(|entity| A {
+        _data: entity.index() as usize,
+    });
+    for entity in [reused, extended, recorded] {
+        assert_eq!(
+            registry.get_component::<A>(entity).unwrap()._data,
+            entity.index() as usize
+        );
+    }
+    registry.apply(&mut commands);
+    assert!(registry.has_components::<(A, B)>(recorded));
+    assert_eq!(registry.iter_entities().count(), 5);
+}
+
+#[test]
+fn registry_test_empty_entities() {
+    let mut registry = Registry::default();
+    let empty = registry.create_empty_entity().unwrap();
+    let other = registry.create_empty_entity().unwrap();
+    assert_ne!(empty, other);
+    assert!(!registry.has_component::<A>(empty));
+    assert_eq!(registry.iter_entities().count(), 2);
+
+    registry.add_component(empty, A { _data: 1 }).unwrap();
+    assert_eq!(registry.get_component::<A>(empty).unwrap()._data, 1);
+    assert_eq!(registry.remove_component::<A>(empty).unwrap()._data, 1);
+    assert!(!registry.has_component::<A>(empty));
+
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry.remove_components::<(B, A)>(entity).unwrap();
+    assert!(registry.add_component(entity, C::default()).is_ok());
+    assert!(registry.remove_component::<C>(entity).is_ok());
+    assert!(registry.remove_component::<C>(entity).is_err());
+    assert_eq!(registry.iter_entities().count(), 3);
+
+    assert!(registry.destroy_entity(other));
+    assert!(registry.destroy_entity(entity));
+    assert!(!registry.destroy_entity(other));
+    assert_eq!(registry.iter_entities().collect::<Vec<_>>(), [empty]);
+
+    let reserved = registry.reserve_entity();
+    registry.flush_reserved_empty();
+    registry.add_component(reserved, B::default()).unwrap();
+    assert!(registry.has_component::<B>(reserved));
+    assert_eq!(registry.iter_entities().count(), 2);
+}
+
+#[test]
+fn registry_test_entity_location() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A::default()).unwrap();
+    let second = registry.create_entity(A::default()).unwrap();
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let empty = registry.create_empty_entity().unwrap();
+
+    assert!(registry.contains(first));
+    assert!(registry.contains(empty));
+    assert!(!registry.contains(Entity::INVALID));
+    assert!(registry.archetype_of(Entity::INVALID).is_none());
+    assert_eq!(registry.entity_location(Entity::INVALID), None);
+
+    let descriptor = registry.archetype_of(entity).unwrap();
+    assert_eq!(descriptor.len(), 2);
+    assert!(descriptor.has_component::<B>());
+    assert_eq!(registry.archetype_of(empty).unwrap().len(), 0);
+
+    let (first_archetype, first_index) = registry.entity_location(first).unwrap();
+    let (second_archetype, second_index) = registry.entity_location(second).unwrap();
+    assert_eq!(first_archetype, second_archetype);
+    assert_eq!(first_index.value(), 0);
+    assert_eq!(second_index.value(), 1);
+    assert_ne!(registry.entity_location(entity).unwrap().0, first_archetype);
+
+    assert!(registry.destroy_entity(first));
+    assert!(!registry.contains(first));
+    assert!(registry.archetype_of(first).is_none());
+    assert_eq!(registry.entity_location(first), None);
+    let (_, moved_index) = registry.entity_location(second).unwrap();
+    assert_eq!(moved_index.value(), 0);
+
+    let reserved = registry.reserve_entity();
+    assert!(!registry.contains(reserved));
+    registry.flush_reserved_empty();
+    assert!(registry.contains(reserved));
+}
+
+#[test]
+fn registry_test_spawn_batch() {
+    let mut registry = Registry::default();
+    let existing = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let entities = registry
+        .spawn_batch((0..300).map(|i| (A { _data: i }, B { _data: i * 2 })))
+        .collect::<Vec<_>>();
+    assert_eq!(entities.len(), 300);
+    for (i, entity) in entities.iter().enumerate() {
+        let (a, b) = registry.get_components::<(A, B)>(*entity).unwrap();
+        assert_eq!(a._data, i);
+        assert_eq!(b._data, i * 2);
+        assert_eq!(
+            registry.entity_location(*entity).unwrap().1.value(),
+            i as u32 + 1
+        );
+    }
+    assert!(registry.contains(existing));
+
+    // Iterators without an exact size hint are spawned as well.
+    assert!(registry.destroy_entity(entities[10]));
+    let filtered = registry
+        .spawn_batch((0..100).filter(|i| i % 2 == 0).map(|i| C { _data: i }))
+        .collect::<Vec<_>>();
+    assert_eq!(filtered.len(), 50);
+    assert_eq!(filtered[0].index(), entities[10].index());
+    for (i, entity) in filtered.iter().enumerate() {
+        assert_eq!(registry.get_component::<C>(*entity).unwrap()._data, i * 2);
+    }
+    assert_eq!(registry.iter_entities().count(), 350);
+
+    assert_eq!(registry.spawn_batch(core::iter::empty::<A>()).count(), 0);
+    assert_eq!(registry.iter_entities().count(), 350);
+}
+
+#[test]
+fn registry_test_spawn_batch_panic() {
+    extern crate std;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut registry = Registry::default();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        registry
+            .spawn_batch((0..10).map(|i| {
+                assert!(i < 4, "Iterator panicked.");
+                A { _data: i }
+            }))
+            .count()
+    }));
+    assert!(result.is_err());
+
+    // Only the entities whose components were written remain.
+    let entities = registry.iter_entities().collect::<Vec<_>>();
+    assert_eq!(entities.len(), 4);
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(registry.get_component::<A>(*entity), Some(&A { _data: i }));
+    }
+    let spawned = registry
+        .spawn_batch((0..6).map(|i| A { _data: i }))
+        .collect::<Vec<_>>();
+    assert_eq!(spawned.len(), 6);
+    assert_eq!(registry.iter_entities().count(), 10);
+}
+
+#[test]
+#[cfg(not(feature = "wide_entity_handles"))]
+fn registry_test_spawn_batch_limit() {
+    // Archetypes hold at most MAX_ENTITIES_PER_ARCHETYPE - 1 entities, as limited by their capacity.
+    let limit = MAX_ENTITIES_PER_ARCHETYPE as usize - 1;
+    let mut registry = Registry::default();
+    assert_eq!(
+        registry
+            .spawn_batch((0..limit - 1).map(|i| A { _data: i }))
+            .count(),
+        limit - 1
+    );
+    let spawned = registry
+        .spawn_batch((0..3).map(|i| A { _data: i }))
+        .collect::<Vec<_>>();
+    assert_eq!(spawned.len(), 1);
+    assert_eq!(
+        registry.get_component::<A>(spawned[0]),
+        Some(&A { _data: 0 })
+    );
+    assert_eq!(
+        registry.spawn_batch(core::iter::once(A::default())).count(),
+        0
+    );
+    assert_eq!(
+        registry.try_create_entity(A::default()).unwrap_err().kind,
+        RegistryErrorKind::LimitExceeded
+    );
+    assert_eq!(registry.iter_entities().count(), limit);
+}
+
+#[test]
+fn registry_test_destroy_matching() {
+    let drops = DropCounter::default();
+    let mut registry = Registry::default();
+    registry.set_removal_log_enabled(true);
+    let counted = registry
+        .spawn_batch((0..10).map(|i| (A::default(), drops.component(i))))
+        .collect::<Vec<_>>();
+    let with_b = registry
+        .spawn_batch((0..5).map(|_| (A::default(), B::default(), drops.component(0))))
+        .collect::<Vec<_>>();
+    let others = registry
+        .spawn_batch((0..3).map(|_| B::default()))
+        .collect::<Vec<_>>();
+
+    // Archetypes not matching the filter closure are left untouched.
+    assert_eq!(
+        registry.destroy_filtered_matching::<DropCounted, _>(
+            |descriptor| !descriptor.has_component::<B>()
+        ),
+        10
+    );
+    assert_eq!(drops.count(), 10);
+    assert!(counted.iter().all(|entity| !registry.contains(*entity)));
+    assert!(with_b.iter().all(|entity| registry.contains(*entity)));
+    assert_eq!(registry.drain_destroyed().count(), 10);
+
+    assert_eq!(registry.destroy_matching::<(A, DropCounted)>(), 5);
+    assert_eq!(drops.count(), 15);
+    assert_eq!(registry.destroy_matching::<DropCounted>(), 0);
+    assert_eq!(registry.iter_entities().collect::<Vec<_>>(), others);
+    assert_eq!(registry.drain_removed::<DropCounted>().count(), 15);
+
+    // Freed slots and cleared archetypes are reused.
+    let entity = registry
+        .create_entity((A::default(), drops.component(3)))
+        .unwrap();
+    assert_eq!(registry.entity_location(entity).unwrap().1.value(), 0);
+    assert_eq!(entity.index(), with_b[4].index());
+    assert_eq!(
+        registry.get_component::<DropCounted>(entity).unwrap()._data,
+        3
+    );
+    assert_eq!(registry.destroy_matching::<B>(), 3);
+    assert_eq!(registry.iter_entities().collect::<Vec<_>>(), [entity]);
+}
+
+#[test]
+fn registry_test_clear() {
+    let mut registry = Registry::default();
+    let entities = registry
+        .spawn_batch((0..20).map(|i| (A { _data: i }, B::default())))
+        .collect::<Vec<_>>();
+    let other = registry.create_entity(C::default()).unwrap();
+    let empty = registry.create_empty_entity().unwrap();
+    assert!(registry.destroy_entity(entities[3]));
+    let reserved = registry.reserve_entity();
+
+    registry.clear();
+    assert_eq!(registry.iter_entities().count(), 0);
+    assert!(!registry.contains(other));
+    assert!(!registry.contains(empty));
+    assert!(entities.iter().all(|entity| !registry.contains(*entity)));
+    assert_eq!(registry.iter_components_matching::<A>().count(), 1);
+    assert!(registry
+        .iter_components_matching::<A>()
+        .all(|slice| slice.is_empty()));
+
+    // Slots are reused in index order, stale handles never alias new entities.
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    assert_eq!(entity.index(), entities[0].index());
+    assert_ne!(entity, entities[0]);
+    assert_eq!(registry.entity_location(entity).unwrap().1.value(), 0);
+
+    // Reserved entities survive clearing.
+    registry.flush_reserved_empty();
+    assert!(registry.contains(reserved));
+    assert_eq!(registry.iter_entities().count(), 2);
+}
+
+#[test]
+fn registry_test_reserve_shrink() {
+    let mut registry = Registry::default();
+    assert!(registry.reserve::<(A, B)>(1000));
+    let archetype = registry
+        .iter_archetypes()
+        .find(|archetype| archetype.descriptor().len() == 2)
+        .unwrap();
+    assert!(archetype.capacity() >= 1000);
+    assert_eq!(archetype.used_bytes(), 0);
+
+    let entities = registry
+        .spawn_batch((0..10).map(|_| (A::default(), B::default())))
+        .collect::<Vec<_>>();
+    let allocated = registry
+        .iter_archetypes()
+        .map(|archetype| archetype.allocated_bytes())
+        .sum::<usize>();
+    registry.shrink_to_fit();
+    assert!(
+        registry
+            .iter_archetypes()
+            .map(|archetype| archetype.allocated_bytes())
+            .sum::<usize>()
+            < allocated
+    );
+    for archetype in registry.iter_archetypes() {
+        assert_eq!(archetype.capacity(), archetype.len().max(1));
+    }
+    assert!(entities
+        .iter()
+        .all(|entity| registry.has_components::<(A, B)>(*entity)));
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    assert_eq!(registry.iter_entities().count(), 11);
+}
+
+#[test]
+fn registry_test_custom_allocator() {
+    use alloc::alloc::Layout;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Default)]
+    struct CountingAllocator {
+        live_bytes: Arc<AtomicUsize>,
+    }
+
+    unsafe impl ArchetypeAllocator for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            self.live_bytes.fetch_add(layout.size(), Ordering::SeqCst);
+            Global.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.live_bytes.fetch_sub(layout.size(), Ordering::SeqCst);
+            Global.dealloc(ptr, layout)
+        }
+    }
+
+    let allocator = CountingAllocator::default();
+    let mut registry = Registry::new_in(allocator.clone());
+    let root_bytes = allocator.live_bytes.load(Ordering::SeqCst);
+    assert!(root_bytes > 0);
+
+    let entities = registry
+        .spawn_batch((0..500).map(|i| (A { _data: i }, B::default())))
+        .collect::<Vec<_>>();
+    let entity = registry.create_entity(C::default()).unwrap();
+    registry.add_component(entity, A::default()).unwrap();
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(registry.get_component::<A>(*entity).unwrap()._data, i);
+    }
+    assert_eq!(
+        allocator.live_bytes.load(Ordering::SeqCst),
+        registry
+            .iter_archetypes()
+            .map(|archetype| archetype.allocated_bytes())
+            .sum::<usize>()
+    );
+
+    let mut commands = CommandBuffer::new(&registry);
+    commands.destroy_entity(entity);
+    registry.apply(&mut commands);
+    registry.shrink_to_fit();
+    assert_eq!(
+        allocator.live_bytes.load(Ordering::SeqCst),
+        registry
+            .iter_archetypes()
+            .map(|archetype| archetype.allocated_bytes())
+            .sum::<usize>()
+    );
+
+    drop(registry);
+    assert_eq!(allocator.live_bytes.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn registry_test_fallible_allocation() {
+    use alloc::alloc::Layout;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone)]
+    struct LimitedAllocator {
+        allocations_left: Arc<AtomicUsize>,
+        live_bytes: Arc<AtomicUsize>,
+    }
+
+    unsafe impl ArchetypeAllocator for LimitedAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let allowed = self
+                .allocations_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok();
+            if !allowed {
+                return core::ptr::null_mut();
+            }
+            self.live_bytes.fetch_add(layout.size(), Ordering::SeqCst);
+            Global.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.live_bytes.fetch_sub(layout.size(), Ordering::SeqCst);
+            Global.dealloc(ptr, layout)
+        }
+    }
+
+    let allocator = LimitedAllocator {
+        allocations_left: Arc::new(AtomicUsize::new(usize::MAX)),
+        live_bytes: Arc::new(AtomicUsize::new(0)),
+    };
+    let allocated_bytes = |registry: &Registry<LimitedAllocator>| {
+        registry
+            .iter_archetypes()
+            .map(|archetype| archetype.allocated_bytes())
+            .sum::<usize>()
+    };
+    let mut registry = Registry::new_in(allocator.clone());
+    let entity = registry.try_create_entity(A { _data: 0 }).unwrap();
+
+    // Creating a new archetype fails, handing back the components.
+    allocator.allocations_left.store(0, Ordering::SeqCst);
+    let error = registry
+        .try_create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap_err();
+    assert_eq!(error.kind, RegistryErrorKind::AllocationFailed);
+    assert_eq!(error.into_components(), (A { _data: 1 }, B { _data: 2 }));
+    let error = registry
+        .try_add_component(entity, B { _data: 3 })
+        .unwrap_err();
+    assert_eq!(error.kind, RegistryErrorKind::AllocationFailed);
+    assert_eq!(error.components, B { _data: 3 });
+    assert!(!registry.has_component::<B>(entity));
+    assert_eq!(
+        registry.try_reserve::<(A, B)>(10).unwrap_err().kind,
+        RegistryErrorKind::AllocationFailed
+    );
+
+    // Growing an archetype fails halfway through, leaving it untouched.
+    let capacity = registry
+        .iter_archetypes()
+        .find(|archetype| archetype.descriptor().len() == 1)
+        .unwrap()
+        .capacity();
+    allocator
+        .allocations_left
+        .store(usize::MAX, Ordering::SeqCst);
+    let mut entities = alloc::vec![entity];
+    for i in 1..capacity as usize {
+        entities.push(registry.try_create_entity(A { _data: i }).unwrap());
+    }
+    allocator.allocations_left.store(1, Ordering::SeqCst);
+    let error = registry
+        .try_create_entity(A {
+            _data: capacity as usize,
+        })
+        .unwrap_err();
+    assert_eq!(error.kind, RegistryErrorKind::AllocationFailed);
+    assert_eq!(
+        registry.try_reserve::<A>(capacity).unwrap_err().kind,
+        RegistryErrorKind::AllocationFailed
+    );
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(registry.get_component::<A>(*entity).unwrap()._data, i);
+    }
+    assert_eq!(
+        allocator.live_bytes.load(Ordering::SeqCst),
+        allocated_bytes(&registry)
+    );
+
+    // Other reasons of failure are reported as well.
+    allocator
+        .allocations_left
+        .store(usize::MAX, Ordering::SeqCst);
+    assert_eq!(
+        registry
+            .try_add_component(Entity::invalid(), B::default())
+            .unwrap_err()
+            .kind,
+        RegistryErrorKind::InvalidEntity
+    );
+    assert_eq!(
+        registry
+            .try_add_component(entity, A::default())
+            .unwrap_err()
+            .kind,
+        RegistryErrorKind::InvalidComponentGroup
+    );
+
+    // Succeeds once memory is available again.
+    registry.try_add_component(entity, B { _data: 3 }).unwrap();
+    registry.try_reserve::<(A, B)>(100).unwrap();
+    registry
+        .try_create_entity(A {
+            _data: capacity as usize,
+        })
+        .unwrap();
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 3 }));
+    assert_eq!(registry.iter_entities().count(), capacity as usize + 1);
+    assert_eq!(
+        allocator.live_bytes.load(Ordering::SeqCst),
+        allocated_bytes(&registry)
+    );
+    drop(registry);
+    assert_eq!(allocator.live_bytes.load(Ordering::SeqCst), 0);
+}
+
+/// A component type sharing the component type id of [`A`].
+#[derive(Debug, PartialEq)]
+struct Alias {
+    _data: [usize; 2],
+}
+impl Component for Alias {
+    const NAME: &'static str = "Alias";
+    const ID: ComponentTypeId = A::ID;
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "Component types `A` and `Alias` share the same component type id.")
+)]
+fn registry_test_component_type_collision() {
+    let mut registry = Registry::default();
+    let entity = registry.create_entity(A::default()).unwrap();
+    let alias = Alias { _data: [1, 2] };
+    let error = registry.try_create_entity(alias).unwrap_err();
+    assert_eq!(
+        error.kind,
+        RegistryErrorKind::ComponentTypeCollision {
+            existing: "A",
+            provided: "Alias",
+        }
+    );
+    assert!(registry.add_component(entity, error.components).is_err());
+    assert!(registry.remove_component::<Alias>(entity).is_err());
+    assert!(!registry.reserve::<(Alias, B)>(10));
+    assert_eq!(registry.get_component::<A>(entity), Some(&A::default()));
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "Component types `A` and `Alias` share the same component type id.")
+)]
+fn registry_test_component_type_collision_lookup() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+
+    // Columns are never resolved by the component type id alone.
+    assert!(!registry.has_component::<Alias>(entity));
+    assert!(!registry.has_components::<(Alias, B)>(entity));
+    assert!(registry.get_component::<Alias>(entity).is_none());
+    assert!(registry.get_components::<(B, Alias)>(entity).is_none());
+    assert!(registry.get_component_mut::<Alias>(entity).is_none());
+    assert!(registry
+        .replace_component(entity, Alias { _data: [1, 2] })
+        .is_err());
+    assert_eq!(registry.iter::<Alias>().count(), 0);
+    assert_eq!(registry.iter_components_matching::<Alias>().count(), 0);
+    assert_eq!(Query::<Read<Alias>>::new().iter(&registry).count(), 0);
+    assert_eq!(
+        Query::<Read<B>, Without<Alias>>::new()
+            .iter(&registry)
+            .count(),
+        1
+    );
+    assert_eq!(registry.get_component::<A>(entity), Some(&A::default()));
+}
+
+#[test]
+fn registry_test_remove_entity_mismatched_group() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+
+    // Groups which do not match the archetype of the entity exactly leave it untouched.
+    assert!(registry.remove_entity::<A>(entity).is_none());
+    assert!(registry.remove_entity::<(A, C)>(entity).is_none());
+    assert!(registry.remove_entity::<(A, B, C)>(entity).is_none());
+    assert_eq!(
+        registry.get_components::<(A, B)>(entity),
+        Some((&A { _data: 1 }, &B { _data: 2 }))
+    );
+
+    assert_eq!(
+        registry.remove_entity::<(B, A)>(entity),
+        Some((B { _data: 2 }, A { _data: 1 }))
+    );
+    assert!(!registry.contains(entity));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn registry_test_bundles() {
+    #[derive(Bundle, Debug, Default, PartialEq)]
+    struct Inner {
+        c: C,
+        a: A,
+    }
+    #[derive(Bundle, Debug, Default, PartialEq)]
+    struct Outer {
+        b: B,
+        inner: Inner,
+    }
+
+    assert_eq!(Outer::DESCRIPTOR.len(), 3);
+    assert!(Outer::DESCRIPTOR
+        .archetype()
+        .has_same_components(<(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype()));
+
+    let mut registry = Registry::default();
+    let bundle = Outer {
+        b: B { _data: 1 },
+        inner: Inner {
+            c: C { _data: 2 },
+            a: A { _data: 3 },
+        },
+    };
+    let entity = registry.create_entity(bundle).unwrap();
+    let tuple = registry
+        .create_entity((C { _data: 2 }, A { _data: 3 }, B { _data: 1 }))
+        .unwrap();
+    assert_eq!(
+        registry.get_components::<Outer>(entity),
+        Some((&B { _data: 1 }, (&C { _data: 2 }, &A { _data: 3 })))
+    );
+    assert_eq!(
+        registry.get_components::<Inner>(tuple),
+        Some((&C { _data: 2 }, &A { _data: 3 }))
+    );
+
+    let (b, (c, _)) = registry.get_components_mut::<Outer>(entity).unwrap();
+    b._data = 4;
+    c._data = 5;
+    assert_eq!(
+        registry.remove_entity::<Outer>(entity),
+        Some(Outer {
+            b: B { _data: 4 },
+            inner: Inner {
+                c: C { _data: 5 },
+                a: A { _data: 3 },
+            },
+        })
+    );
+    assert_eq!(
+        registry.remove_components::<Inner>(tuple),
+        Ok(Inner {
+            c: C { _data: 2 },
+            a: A { _data: 3 },
+        })
+    );
+    assert_eq!(registry.get_component::<B>(tuple), Some(&B { _data: 1 }));
+}
+
+#[cfg(not(feature = "wide_entity_handles"))]
+#[test]
+fn registry_test_retired_slots() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A::default()).unwrap();
+    let mut entity = first;
+    for version in 0..=MAX_ENTITY_VERSION_VALUE {
+        assert_eq!(entity.index(), first.index());
+        assert_eq!(entity.version(), version);
+        assert!(registry.destroy_entity(entity));
+        entity = registry.create_entity(A::default()).unwrap();
+    }
+    assert_eq!(registry.retired_slot_count(), 1);
+    assert_ne!(entity.index(), first.index());
+    assert!(!registry.has_component::<A>(first));
+    assert_eq!(registry.iter_entities().collect::<Vec<_>>(), [entity]);
+}
@@ -1,4 +1,6 @@
 use crate::*;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct A {
@@ -99,3 +101,31 @@ impl Drop for DropLogC {
         std::println!("Dropping C: {:#?}", self as *const Self);
     }
 }
+/// Counts how often the [`DropCounted`] components created by it have been dropped.
+#[derive(Clone, Default, Debug)]
+pub struct DropCounter(Arc<AtomicUsize>);
+impl DropCounter {
+    pub fn component(&self, _data: usize) -> DropCounted {
+        DropCounted {
+            _data,
+            counter: self.0.clone(),
+        }
+    }
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+#[derive(Debug)]
+pub struct DropCounted {
+    pub _data: usize,
+    counter: Arc<AtomicUsize>,
+}
+impl Component for DropCounted {
+    const NAME: &'static str = "DropCounted";
+    const ID: ComponentTypeId = ComponentTypeId::from_u16(7);
+}
+impl Drop for DropCounted {
+    fn drop(&mut self) {
+        self.counter.fetch_add(1, Ordering::SeqCst);
+    }
+}